@@ -251,7 +251,7 @@ impl SubCommand {
             Self::Config(c) => config::cmd(c),
             Self::Generate(g) => generate::cmd(g),
             Self::GenerateSchema => generate_schema::cmd(),
-            Self::Graph(g) => graph::cmd(g),
+            Self::Graph(g) => graph::cmd(g).await,
             Self::List(l) => list::cmd(l),
             #[cfg(windows)]
             Self::Service(s) => service::cmd(s),
@@ -70,100 +70,101 @@ pub(super) mod process {
             format::deserialize(&value, format).map(|builder| (builder, warnings))
         }
 
-        /// Helper method used by other methods to recursively handle file/dir loading, merging
-        /// values against a provided TOML `Table`.
+        /// Helper method used by other methods to handle file/dir loading, merging values
+        /// against a provided TOML `Table`. With `recurse: true`, the sub-folders are loaded
+        /// with an explicit stack of the directories being loaded rather than by recursion, so
+        /// deeply nested config dirs don't overflow the call stack.
         fn load_dir_into(
             &mut self,
             path: &Path,
             result: &mut Table,
             recurse: bool,
         ) -> Result<Vec<String>, Vec<String>> {
-            let mut errors = Vec::new();
-            let mut warnings = Vec::new();
-            let readdir = read_dir(path)?;
-
-            let mut files = Vec::new();
-            let mut folders = Vec::new();
-
-            for entry in readdir {
-                match entry {
-                    Ok(item) => {
-                        let entry = item.path();
-                        if entry.is_file() {
-                            files.push(entry);
-                        } else if entry.is_dir() {
-                            // do not load directories when the directory starts with a '.'
-                            if !entry
-                                .file_name()
-                                .and_then(|name| name.to_str())
-                                .map(|name| name.starts_with('.'))
-                                .unwrap_or(false)
-                            {
-                                folders.push(entry);
+            let root = DirLoad::read(path, std::mem::take(result), Vec::new(), None, recurse);
+            let mut stack = match root {
+                Ok(root) => vec![root],
+                Err((table, errors)) => {
+                    *result = table;
+                    return Err(errors);
+                }
+            };
+
+            loop {
+                let dir = stack
+                    .last_mut()
+                    .expect("the root is only popped at the end");
+
+                let child = if let Some(entry) = dir.files.next() {
+                    // If the file doesn't contain a known extension, skip it.
+                    let format = match Format::from_path(&entry) {
+                        Ok(format) => format,
+                        _ => continue,
+                    };
+
+                    match self.load_file(&entry, format) {
+                        Ok(Some((name, inner, warns))) => {
+                            // A sub-folder by the same name as the component is merged into it.
+                            let subdir = entry
+                                .parent()
+                                .map(|p| p.join(&name))
+                                .filter(|subdir| recurse && subdir.is_dir());
+                            match subdir {
+                                Some(subdir) => Some(DirLoad::read(
+                                    &subdir,
+                                    inner,
+                                    warns,
+                                    Some(Nested::Merge(name)),
+                                    true,
+                                )),
+                                None => {
+                                    dir.merge(name, inner, warns);
+                                    None
+                                }
                             }
                         }
+                        Ok(None) => None,
+                        Err(errs) => {
+                            dir.errors.extend(errs);
+                            None
+                        }
                     }
-                    Err(err) => {
-                        errors.push(format!(
-                            "Could not read entry in config dir: {:?}, {}.",
-                            path, err
-                        ));
+                } else if let Some(entry) = dir.folders.next() {
+                    match component_name(&entry) {
+                        Ok(name) if !dir.table.contains_key(&name) => Some(DirLoad::read(
+                            &entry,
+                            Table::new(),
+                            Vec::new(),
+                            Some(Nested::Insert(name)),
+                            true,
+                        )),
+                        _ => None,
                     }
-                };
-            }
-
-            for entry in files {
-                // If the file doesn't contain a known extension, skip it.
-                let format = match Format::from_path(&entry) {
-                    Ok(format) => format,
-                    _ => continue,
-                };
-
-                let loaded = if recurse {
-                    self.load_file_recursive(&entry, format)
                 } else {
-                    self.load_file(&entry, format)
-                };
-
-                match loaded {
-                    Ok(Some((name, inner, warns))) => {
-                        if let Err(errs) = merge_with_value(result, name, Value::Table(inner)) {
-                            errors.extend(errs);
-                        } else {
-                            warnings.extend(warns);
+                    // Every entry is loaded, so the directory is handed to the one it is in.
+                    let done = stack.pop().expect("the directory was just looked at");
+                    match stack.last_mut() {
+                        Some(parent) => parent.finish(done),
+                        None => {
+                            *result = done.table;
+                            return if done.errors.is_empty() {
+                                Ok(done.warnings)
+                            } else {
+                                Err(done.errors)
+                            };
                         }
                     }
-                    Ok(None) => {}
-                    Err(errs) => {
-                        errors.extend(errs);
-                    }
-                }
-            }
+                    continue;
+                };
 
-            // Only descend into folders if `recurse: true`.
-            if recurse {
-                for entry in folders {
-                    if let Ok(name) = component_name(&entry) {
-                        if !result.contains_key(&name) {
-                            match self.load_dir(&entry, true) {
-                                Ok((table, warns)) => {
-                                    result.insert(name, Value::Table(table));
-                                    warnings.extend(warns);
-                                }
-                                Err(errs) => {
-                                    errors.extend(errs);
-                                }
-                            }
-                        }
+                match child {
+                    Some(Ok(child)) => stack.push(child),
+                    Some(Err((_, errs))) => {
+                        let dir = stack.last_mut().expect("the directory was just looked at");
+                        dir.errors.extend(errs);
                     }
+                    None => {}
                 }
             }
-
-            if errors.is_empty() {
-                Ok(warnings)
-            } else {
-                Err(errors)
-            }
         }
 
         /// Loads and deserializes a file into a TOML `Table`.
@@ -180,27 +181,8 @@ pub(super) mod process {
             }
         }
 
-        /// Loads a file, and if the path provided contains a sub-folder by the same name as the
-        /// component, descend into it recursively, returning a TOML `Table`.
-        fn load_file_recursive(
-            &mut self,
-            path: &Path,
-            format: Format,
-        ) -> Result<Option<(String, Table, Vec<String>)>, Vec<String>> {
-            if let Some((name, mut table, mut warnings)) = self.load_file(path, format)? {
-                if let Some(subdir) = path.parent().map(|p| p.join(&name)) {
-                    if subdir.is_dir() && subdir.exists() {
-                        warnings.extend(self.load_dir_into(&subdir, &mut table, true)?);
-                    }
-                }
-                Ok(Some((name, table, warnings)))
-            } else {
-                Ok(None)
-            }
-        }
-
         /// Loads a directory (optionally, recursively), returning a TOML `Table`. This will
-        /// create an initial `Table` and pass it into `load_dir_into` for recursion handling.
+        /// create an initial `Table` and pass it into `load_dir_into` to fill in.
         fn load_dir(
             &mut self,
             path: &Path,
@@ -218,6 +200,110 @@ pub(super) mod process {
     }
 }
 
+/// How a nested directory's `Table` joins the one for the directory it is in.
+enum Nested {
+    /// The directory shares its name with a component file, and is merged into that file's
+    /// `Table` before it is merged in under `name`.
+    Merge(String),
+    /// The directory is a component namespace of its own, inserted under `name`.
+    Insert(String),
+}
+
+/// A directory partway through being loaded by `load_dir_into`, holding the entries it has left
+/// and what has been loaded from the rest.
+struct DirLoad {
+    files: std::vec::IntoIter<PathBuf>,
+    folders: std::vec::IntoIter<PathBuf>,
+    table: Table,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+    /// How to join the directory it is in, or `None` for the directory loading started from.
+    nested: Option<Nested>,
+}
+
+impl DirLoad {
+    /// Lists the entries of the directory at `path`, to be loaded into `table`. Sub-folders are
+    /// only listed with `recurse: true`, and never ones starting with a '.'. If the directory
+    /// can't be read, `table` is returned along with the error.
+    fn read(
+        path: &Path,
+        table: Table,
+        warnings: Vec<String>,
+        nested: Option<Nested>,
+        recurse: bool,
+    ) -> Result<Self, (Table, Vec<String>)> {
+        let readdir = match read_dir(path) {
+            Ok(readdir) => readdir,
+            Err(errors) => return Err((table, errors)),
+        };
+
+        let mut errors = Vec::new();
+        let mut files = Vec::new();
+        let mut folders = Vec::new();
+
+        for entry in readdir {
+            match entry {
+                Ok(item) => {
+                    let entry = item.path();
+                    if entry.is_file() {
+                        files.push(entry);
+                    } else if recurse && entry.is_dir() {
+                        // do not load directories when the directory starts with a '.'
+                        if !entry
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .map(|name| name.starts_with('.'))
+                            .unwrap_or(false)
+                        {
+                            folders.push(entry);
+                        }
+                    }
+                }
+                Err(err) => {
+                    errors.push(format!(
+                        "Could not read entry in config dir: {:?}, {}.",
+                        path, err
+                    ));
+                }
+            };
+        }
+
+        Ok(Self {
+            files: files.into_iter(),
+            folders: folders.into_iter(),
+            table,
+            warnings,
+            errors,
+            nested,
+        })
+    }
+
+    /// Merges a loaded component `Table` in under `name`, keeping its warnings if it merges.
+    fn merge(&mut self, name: String, inner: Table, warnings: Vec<String>) {
+        if let Err(errs) = merge_with_value(&mut self.table, name, Value::Table(inner)) {
+            self.errors.extend(errs);
+        } else {
+            self.warnings.extend(warnings);
+        }
+    }
+
+    /// Joins a nested directory that has been loaded, or takes its errors if it failed.
+    fn finish(&mut self, done: Self) {
+        if !done.errors.is_empty() {
+            self.errors.extend(done.errors);
+            return;
+        }
+        match done.nested {
+            Some(Nested::Merge(name)) => self.merge(name, done.table, done.warnings),
+            Some(Nested::Insert(name)) => {
+                self.table.insert(name, Value::Table(done.table));
+                self.warnings.extend(done.warnings);
+            }
+            None => unreachable!("only the directory loading started from has no parent"),
+        }
+    }
+}
+
 /// `Loader` represents the public part of the loading interface. Includes methods for loading
 /// from a file or folder, and accessing the final deserialized `T` value via the `take` method.
 pub trait Loader<T>: process::Process
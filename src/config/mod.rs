@@ -42,6 +42,7 @@ pub mod watcher;
 
 pub use builder::ConfigBuilder;
 pub use cmd::{cmd, Opts};
+pub(crate) use compiler::expand_globs;
 pub use diff::ConfigDiff;
 pub use enrichment_table::{EnrichmentTableConfig, EnrichmentTableOuter};
 pub use format::{Format, FormatHint};
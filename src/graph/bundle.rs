@@ -0,0 +1,201 @@
+//! Assembles several views of a topology into one self-contained HTML page.
+
+use std::io::{self, Write};
+
+use super::{ComponentIndex, ComponentKind, Direction, Topology};
+
+/// One titled part of the bundle.
+#[derive(Clone, Debug)]
+pub struct Section {
+    /// The heading shown above the section.
+    pub title: String,
+
+    /// What the section shows.
+    pub body: SectionBody,
+}
+
+/// The content of a [`Section`].
+#[derive(Clone, Debug)]
+pub enum SectionBody {
+    /// An SVG image, inlined into the page.
+    Svg(Vec<u8>),
+
+    /// A table of how much of the pipeline each source feeds.
+    SourceStats(Vec<SourceStats>),
+}
+
+/// What a single source feeds, directly and transitively.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceStats {
+    /// The source's component ID.
+    pub id: String,
+
+    /// The source's `type`, if it is in the index.
+    pub component_type: Option<&'static str>,
+
+    /// The number of components reading from the source directly.
+    pub outputs: usize,
+
+    /// The number of transforms its events can pass through.
+    pub transforms: usize,
+
+    /// The IDs of the sinks its events can reach, in node order.
+    pub sinks: Vec<String>,
+}
+
+/// Computes the stats for every source in the topology, in node order, following the edges
+/// `index` has between the topology's components.
+pub fn source_stats(topology: &Topology, index: &ComponentIndex) -> Vec<SourceStats> {
+    let ids = topology.ids();
+    topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Source)
+        .map(|source| {
+            let reached = index.reachable([source.id.clone()], Direction::Downstream, |id| {
+                ids.contains(id)
+            });
+            let downstream = topology
+                .nodes
+                .iter()
+                .filter(|node| reached.contains(&node.id))
+                .collect::<Vec<_>>();
+            SourceStats {
+                id: source.id.id().to_owned(),
+                component_type: index
+                    .get(&source.id)
+                    .map(|component| component.component_type),
+                outputs: index
+                    .adjacent(&source.id, Direction::Downstream)
+                    .iter()
+                    .filter(|id| ids.contains(id))
+                    .count(),
+                transforms: downstream
+                    .iter()
+                    .filter(|node| node.kind == ComponentKind::Transform)
+                    .count(),
+                sinks: downstream
+                    .iter()
+                    .filter(|node| node.kind == ComponentKind::Sink)
+                    .map(|node| node.id.id().to_owned())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Writes the sections as an HTML page that needs no scripts or external resources.
+pub fn write_bundle<W: Write + ?Sized>(
+    title: &str,
+    sections: &[Section],
+    w: &mut W,
+) -> io::Result<()> {
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html>")?;
+    writeln!(w, "<head>")?;
+    writeln!(w, "<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>{}</title>", escape(title))?;
+    writeln!(
+        w,
+        "<style>body {{ font-family: sans-serif; margin: 2em; }} \
+         section {{ margin-bottom: 3em; }} svg {{ max-width: 100%; height: auto; }} \
+         table {{ border-collapse: collapse; }} \
+         th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }}</style>"
+    )?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    writeln!(w, "<h1>{}</h1>", escape(title))?;
+
+    for section in sections {
+        writeln!(w, "<section>")?;
+        writeln!(w, "<h2>{}</h2>", escape(&section.title))?;
+        match &section.body {
+            SectionBody::Svg(svg) => {
+                w.write_all(strip_prolog(svg))?;
+                if !svg.ends_with(b"\n") {
+                    writeln!(w)?;
+                }
+            }
+            SectionBody::SourceStats(stats) => write_stats(stats, w)?,
+        }
+        writeln!(w, "</section>")?;
+    }
+
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")
+}
+
+fn write_stats<W: Write + ?Sized>(stats: &[SourceStats], w: &mut W) -> io::Result<()> {
+    if stats.is_empty() {
+        return writeln!(w, "<p>No sources.</p>");
+    }
+
+    writeln!(w, "<table>")?;
+    writeln!(
+        w,
+        "<tr><th>Source</th><th>Type</th><th>Outputs</th><th>Transforms reached</th><th>Sinks reached</th></tr>"
+    )?;
+    for source in stats {
+        writeln!(
+            w,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&source.id),
+            source.component_type.unwrap_or_default(),
+            source.outputs,
+            source.transforms,
+            escape(&source.sinks.join(", "))
+        )?;
+    }
+    writeln!(w, "</table>")
+}
+
+/// Drops the XML declaration and doctype GraphViz puts before the `<svg>` element, which are
+/// invalid inside an HTML body.
+fn strip_prolog(svg: &[u8]) -> &[u8] {
+    svg.windows(4)
+        .position(|window| window == b"<svg")
+        .map_or(svg, |start| &svg[start..])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inlines_svg_without_prolog() {
+        let sections = [
+            Section {
+                title: "Full <pipeline>".to_owned(),
+                body: SectionBody::Svg(
+                    b"<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg><g/></svg>\n".to_vec(),
+                ),
+            },
+            Section {
+                title: "Sources".to_owned(),
+                body: SectionBody::SourceStats(vec![SourceStats {
+                    id: "in".to_owned(),
+                    component_type: Some("demo_logs"),
+                    outputs: 2,
+                    transforms: 1,
+                    sinks: vec!["a".to_owned(), "b".to_owned()],
+                }]),
+            },
+        ];
+
+        let mut output = Vec::new();
+        write_bundle("vector", &sections, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("<h2>Full &lt;pipeline&gt;</h2>\n<svg><g/></svg>\n"));
+        assert!(!output.contains("<?xml"));
+        assert!(output
+            .contains("<tr><td>in</td><td>demo_logs</td><td>2</td><td>1</td><td>a, b</td></tr>"));
+    }
+}
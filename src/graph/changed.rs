@@ -0,0 +1,230 @@
+//! Finds the components whose definition changed since a git revision, by comparing each config
+//! file against its contents at that revision.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+
+use super::files::{config_files, Defines};
+use crate::config::{self, ComponentKey, ConfigBuilder, ConfigPath, Format};
+
+/// Collects the components defined differently in `paths` than at `rev`, including those that
+/// are new since then. Components that were removed aren't in the graph, so they aren't listed.
+pub fn changed_since(paths: &[ConfigPath], rev: &str) -> Result<HashSet<ComponentKey>, String> {
+    let mut changed = HashSet::new();
+    let mut verified = HashSet::new();
+    for file in config_files(paths) {
+        let dir = parent(&file.path);
+        if verified.insert(dir.to_owned()) {
+            verify(dir, rev)?;
+        }
+
+        let new = fs::read(&file.path)
+            .map_err(|error| format!("Failed to read {:?}: {}", file.path, error))?;
+        let old = show(&file.path, rev)?;
+        if old.as_ref() == Some(&new) {
+            continue;
+        }
+
+        match file.defines {
+            // The whole file is part of the component's definition.
+            Defines::Component(_, id) => {
+                changed.insert(id);
+            }
+            Defines::Other => {}
+            Defines::Config => {
+                let new = parse(&new, file.format, &file.path)?;
+                // A file that didn't parse at `rev` can't say what it defined, so everything in
+                // it counts as changed.
+                let old = old
+                    .and_then(|old| parse(&old, file.format, &file.path).ok())
+                    .unwrap_or_default();
+                changed.extend(changed_components(&old, &new));
+            }
+        }
+    }
+    Ok(changed)
+}
+
+fn parse(contents: &[u8], format: Format, path: &Path) -> Result<ConfigBuilder, String> {
+    config::load(contents, format)
+        .map(|(builder, _)| builder)
+        .map_err(|errors| format!("Failed to parse {:?}: {}", path, errors.join(", ")))
+}
+
+/// The components of `new` that aren't in `old`, or are configured differently there.
+fn changed_components(old: &ConfigBuilder, new: &ConfigBuilder) -> Vec<ComponentKey> {
+    fn differs<T: Serialize>(old: Option<&T>, new: &T) -> bool {
+        old.map_or(true, |old| {
+            serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+        })
+    }
+
+    let sources = new
+        .sources
+        .iter()
+        .filter(|(id, source)| differs(old.sources.get(*id), *source));
+    let transforms = new
+        .transforms
+        .iter()
+        .filter(|(id, transform)| differs(old.transforms.get(*id), *transform));
+    let sinks = new
+        .sinks
+        .iter()
+        .filter(|(id, sink)| differs(old.sinks.get(*id), *sink));
+    sources
+        .map(|(id, _)| id)
+        .chain(transforms.map(|(id, _)| id))
+        .chain(sinks.map(|(id, _)| id))
+        .cloned()
+        .collect()
+}
+
+/// Checks that `rev` names a commit in the repository `dir` belongs to.
+fn verify(dir: &Path, rev: &str) -> Result<(), String> {
+    let output = git(dir)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", rev))
+        .output()
+        .map_err(|error| format!("Failed to run `git`, make sure it is installed: {}", error))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{:?} is not a commit in the git repository containing {:?}.",
+            rev, dir
+        ))
+    }
+}
+
+/// The contents of `path` at `rev`, or `None` if it didn't exist then.
+fn show(path: &Path, rev: &str) -> Result<Option<Vec<u8>>, String> {
+    let dir = parent(path);
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("{:?} is not a file.", path))?;
+    // `./` makes the path relative to `dir` rather than to the root of the repository.
+    let object = format!("{}:./{}", rev, name.to_string_lossy());
+
+    let exists = git(dir)
+        .args(["cat-file", "-e", &object])
+        .output()
+        .map_err(|error| format!("Failed to run `git`: {}", error))?;
+    if !exists.status.success() {
+        return Ok(None);
+    }
+
+    let output = git(dir)
+        .args(["cat-file", "blob", &object])
+        .output()
+        .map_err(|error| format!("Failed to run `git`: {}", error))?;
+    if output.status.success() {
+        Ok(Some(output.stdout))
+    } else {
+        Err(format!(
+            "Failed to read {:?} at {:?}: {}",
+            path,
+            rev,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// The directory `path` is in, which is `.` for a bare file name.
+fn parent(path: &Path) -> &Path {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+fn git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn commit(dir: &Path) {
+        for args in [
+            &["init", "--quiet"][..],
+            &["add", "."],
+            &[
+                "-c",
+                "user.name=vector",
+                "-c",
+                "user.email=vector@example.com",
+                "commit",
+                "--quiet",
+                "--message",
+                "config",
+            ],
+        ] {
+            assert!(git(dir).args(args).status().unwrap().success());
+        }
+    }
+
+    #[test]
+    fn finds_changed_and_added_components() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("vector.toml");
+        fs::write(
+            &path,
+            indoc! {r#"
+                [sources.in]
+                type = "test_basic"
+
+                [transforms.parse]
+                type = "test_basic"
+                inputs = ["in"]
+                suffix = "x"
+                increase = 1.0
+
+                [sinks.out]
+                type = "test_basic"
+                inputs = ["parse"]
+            "#},
+        )
+        .unwrap();
+        commit(temp.path());
+
+        fs::write(
+            &path,
+            indoc! {r#"
+                [sources.in]
+                type = "test_basic"
+
+                # Comments and formatting don't count as changes.
+                [transforms.parse]
+                type   = "test_basic"
+                inputs = ["in"]
+                suffix = "y"
+                increase = 1.0
+
+                [sinks.out]
+                type = "test_basic"
+                inputs = ["parse"]
+
+                [sinks.archive]
+                type = "test_basic"
+                inputs = ["in"]
+            "#},
+        )
+        .unwrap();
+
+        let paths = [ConfigPath::File(path, None)];
+        let expected = ["parse", "archive"]
+            .into_iter()
+            .map(ComponentKey::from)
+            .collect::<HashSet<_>>();
+        assert_eq!(changed_since(&paths, "HEAD").unwrap(), expected);
+        assert!(changed_since(&paths, "no-such-branch").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+//! Copies rendered graphs to the system clipboard using the platform's clipboard command.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// The commands tried in order to copy standard input to the clipboard, with their arguments.
+#[cfg(target_os = "macos")]
+const TOOLS: &[(&str, &[&str])] = &[("pbcopy", &[])];
+#[cfg(windows)]
+const TOOLS: &[(&str, &[&str])] = &[("clip", &[])];
+#[cfg(not(any(target_os = "macos", windows)))]
+const TOOLS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Copies `text` to the clipboard with the first of the platform's clipboard commands that is
+/// installed and can reach a clipboard.
+pub fn copy(text: &[u8]) -> Result<(), String> {
+    copy_with(TOOLS, text)
+}
+
+fn copy_with(tools: &[(&str, &[&str])], text: &[u8]) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for (tool, args) in tools {
+        match pipe_to(tool, args, text) {
+            Ok(()) => return Ok(()),
+            Err(error) => errors.push(format!("`{}`: {}", tool, error)),
+        }
+    }
+    Err(format!("No clipboard is available ({})", errors.join(", ")))
+}
+
+fn pipe_to(tool: &str, args: &[&str], text: &[u8]) -> io::Result<()> {
+    // The tools may keep running in the background to serve the clipboard, so their output
+    // isn't read.
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let written = child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(text);
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("exited with {}", status),
+        ));
+    }
+    written
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_through_to_a_working_tool() {
+        assert!(copy_with(&[("vector-no-such-tool", &[]), ("cat", &[])], b"graph").is_ok());
+
+        let error =
+            copy_with(&[("vector-no-such-tool", &[]), ("false", &[])], b"graph").unwrap_err();
+        assert!(error.contains("`vector-no-such-tool`"), "{}", error);
+        assert!(error.contains("`false`: exited with"), "{}", error);
+    }
+}
@@ -0,0 +1,166 @@
+//! Groups components into clusters, drawn as labeled boxes around their members.
+
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+
+use super::{ClusterBy, ComponentIndex, ComponentKind, Topology};
+use crate::config::ComponentKey;
+
+/// The characters that end an ID's prefix with `--cluster-by prefix`.
+const PREFIX_SEPARATORS: &[char] = &['_', '-', '.'];
+
+/// A named group of components.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cluster {
+    /// The label drawn on the cluster.
+    pub name: String,
+
+    /// The components in the cluster, in declaration order. Renderers skip members that aren't
+    /// in the topology they draw.
+    pub members: Vec<ComponentKey>,
+}
+
+impl Cluster {
+    /// Whether any of the cluster's members is in `topology`.
+    pub fn is_drawn_in(&self, topology: &Topology) -> bool {
+        topology
+            .nodes
+            .iter()
+            .any(|node| self.members.contains(&node.id))
+    }
+
+    /// How many of the cluster's members are in `topology`.
+    pub fn drawn_len(&self, topology: &Topology) -> usize {
+        let drawn = topology
+            .nodes
+            .iter()
+            .map(|node| &node.id)
+            .collect::<HashSet<_>>();
+        self.members
+            .iter()
+            .filter(|member| drawn.contains(member))
+            .count()
+    }
+}
+
+/// Groups every component in the config, in the order their first member was declared.
+///
+/// Clusters are built from the whole config rather than the topology being drawn, so they stay
+/// the same across filtered views. Components without a prefix aren't put in any cluster.
+pub fn clusters(index: &ComponentIndex, by: ClusterBy) -> Vec<Cluster> {
+    let mut clusters = IndexMap::<String, Vec<ComponentKey>>::new();
+    for (id, component) in index.iter() {
+        let name = match by {
+            ClusterBy::Prefix => match id.id().split_once(PREFIX_SEPARATORS) {
+                Some((prefix, _)) if !prefix.is_empty() => prefix.to_owned(),
+                _ => continue,
+            },
+            ClusterBy::Type => component.component_type.to_owned(),
+        };
+        clusters.entry(name).or_default().push(id.clone());
+    }
+
+    clusters
+        .into_iter()
+        .map(|(name, members)| Cluster { name, members })
+        .collect()
+}
+
+/// The label of the swimlane of components reached from more than one source.
+pub const SHARED_SWIMLANE: &str = "multiple sources";
+
+/// Every source in a swimlane of its own, along with the components only it reaches, for
+/// `--swimlanes`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Swimlanes {
+    /// The lane of each source, in node order, listing the source followed by the components
+    /// only it reaches.
+    pub lanes: IndexMap<ComponentKey, Vec<ComponentKey>>,
+
+    /// The components reached from more than one source, in node order.
+    pub shared: Vec<ComponentKey>,
+}
+
+impl Swimlanes {
+    /// Assigns the components of `topology` to swimlanes. Components no source reaches aren't
+    /// put in any lane.
+    ///
+    /// Unlike [`clusters`], lanes are built from the topology being drawn, since which sources
+    /// reach a component depends on the edges left after filtering.
+    pub fn new(topology: &Topology) -> Self {
+        let mut outputs = HashMap::<_, Vec<_>>::new();
+        for edge in &topology.edges {
+            outputs.entry(&edge.source).or_default().push(&edge.target);
+        }
+
+        let mut lanes = IndexMap::new();
+        let mut reached_from = HashMap::<_, Vec<_>>::new();
+        let sources = topology
+            .nodes
+            .iter()
+            .filter(|node| node.kind == ComponentKind::Source);
+        for source in sources {
+            lanes.insert(source.id.clone(), Vec::new());
+            let mut pending = vec![&source.id];
+            let mut reached = HashSet::from([&source.id]);
+            while let Some(id) = pending.pop() {
+                for &target in outputs.get(id).into_iter().flatten() {
+                    if reached.insert(target) {
+                        pending.push(target);
+                    }
+                }
+            }
+            for id in reached {
+                reached_from.entry(id).or_default().push(&source.id);
+            }
+        }
+
+        let mut shared = Vec::new();
+        for node in &topology.nodes {
+            match reached_from.get(&node.id).map(Vec::as_slice) {
+                Some([source]) => lanes[*source].push(node.id.clone()),
+                Some([]) | None => {}
+                Some(_) => shared.push(node.id.clone()),
+            }
+        }
+
+        Self { lanes, shared }
+    }
+
+    /// The lanes as clusters to draw, each labeled with its source's ID, followed by the shared
+    /// lane if any component is in it. Renderers tell clusters apart by position rather than
+    /// label, so a source that happens to be named like the shared lane still gets its own.
+    pub fn into_clusters(self) -> Vec<Cluster> {
+        let shared = (!self.shared.is_empty()).then(|| Cluster {
+            name: SHARED_SWIMLANE.to_owned(),
+            members: self.shared,
+        });
+        self.lanes
+            .into_iter()
+            .map(|(source, members)| Cluster {
+                name: source.id().to_owned(),
+                members,
+            })
+            .chain(shared)
+            .collect()
+    }
+}
+
+/// The indices of the edges in `topology` whose endpoints aren't in the same cluster, including
+/// those with an endpoint in no cluster at all. Edges between two components outside every
+/// cluster don't cross a boundary.
+pub fn cross_cluster_edges(clusters: &[Cluster], topology: &Topology) -> Vec<usize> {
+    let cluster_of = clusters
+        .iter()
+        .enumerate()
+        .flat_map(|(index, cluster)| cluster.members.iter().map(move |member| (member, index)))
+        .collect::<HashMap<_, _>>();
+    topology
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, edge)| cluster_of.get(&edge.source) != cluster_of.get(&edge.target))
+        .map(|(position, _)| position)
+        .collect()
+}
@@ -0,0 +1,210 @@
+//! Compares two topologies, for reviewing how a config change rewires a pipeline.
+
+use std::io::{self, Write};
+
+use super::{Edge, Node, Style, Styles, Topology};
+
+const ADDED_COLOR: &str = "green";
+const REMOVED_COLOR: &str = "red";
+
+/// The nodes and edges that differ between two topologies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TopologyDiff {
+    /// Nodes only in the new topology, in its order.
+    pub added_nodes: Vec<Node>,
+
+    /// Nodes only in the old topology, in its order.
+    pub removed_nodes: Vec<Node>,
+
+    /// Edges only in the new topology, in its order.
+    pub added_edges: Vec<Edge>,
+
+    /// Edges only in the old topology, in its order.
+    pub removed_edges: Vec<Edge>,
+}
+
+impl TopologyDiff {
+    /// Compares `new` against `old`. A component whose kind changed counts as removed and added.
+    pub fn new(old: &Topology, new: &Topology) -> Self {
+        let missing_nodes = |from: &Topology, other: &Topology| {
+            from.nodes
+                .iter()
+                .filter(|node| !other.nodes.contains(node))
+                .cloned()
+                .collect()
+        };
+        let missing_edges = |from: &Topology, other: &Topology| {
+            from.edges
+                .iter()
+                .filter(|edge| !other.edges.contains(edge))
+                .cloned()
+                .collect()
+        };
+
+        Self {
+            added_nodes: missing_nodes(new, old),
+            removed_nodes: missing_nodes(old, new),
+            added_edges: missing_edges(new, old),
+            removed_edges: missing_edges(old, new),
+        }
+    }
+
+    /// Whether the topologies are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+
+    /// Overlays the removed nodes and edges onto `new`, styling additions in green and removals
+    /// in dashed red so the whole change can be drawn as one graph.
+    pub fn overlay(&self, new: &Topology) -> (Topology, Styles) {
+        let mut topology = new.clone();
+        let mut styles = Styles::default();
+
+        for node in &self.added_nodes {
+            styles
+                .nodes
+                .insert(node.id.clone(), Style::color(ADDED_COLOR));
+        }
+        for node in &self.removed_nodes {
+            styles
+                .nodes
+                .insert(node.id.clone(), Style::color(REMOVED_COLOR).dashed());
+        }
+        // Keep nodes grouped by kind with the removed ones after the remaining ones of their kind.
+        topology.nodes.extend(self.removed_nodes.iter().cloned());
+        topology.nodes.sort_by_key(|node| node.kind);
+
+        for (index, edge) in topology.edges.iter().enumerate() {
+            if self.added_edges.contains(edge) {
+                styles.edges.insert(index, Style::color(ADDED_COLOR));
+            }
+        }
+        for edge in &self.removed_edges {
+            styles
+                .edges
+                .insert(topology.edges.len(), Style::color(REMOVED_COLOR).dashed());
+            topology.edges.push(edge.clone());
+        }
+
+        (topology, styles)
+    }
+
+    /// Writes one line per change, such as `+ added edge a -> b` or `- removed node c`.
+    pub fn write_text<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        for node in &self.removed_nodes {
+            writeln!(w, "- removed node {}", node.id)?;
+        }
+        for node in &self.added_nodes {
+            writeln!(w, "+ added node {}", node.id)?;
+        }
+        for edge in &self.removed_edges {
+            writeln!(w, "- removed edge {}", edge_text(edge))?;
+        }
+        for edge in &self.added_edges {
+            writeln!(w, "+ added edge {}", edge_text(edge))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats an edge the way its input is written in a config, e.g. `route.errors -> out`.
+fn edge_text(edge: &Edge) -> String {
+    match &edge.port {
+        Some(port) => format!("{}.{} -> {}", edge.source, port, edge.target),
+        None => format!("{} -> {}", edge.source, edge.target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ComponentKind;
+
+    fn node(id: &str, kind: ComponentKind) -> Node {
+        Node {
+            id: id.into(),
+            kind,
+            label: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> Edge {
+        Edge {
+            source: source.into(),
+            target: target.into(),
+            port: None,
+        }
+    }
+
+    fn fixtures() -> (Topology, Topology) {
+        let old = Topology {
+            nodes: vec![
+                node("in", ComponentKind::Source),
+                node("parse", ComponentKind::Transform),
+                node("out", ComponentKind::Sink),
+            ],
+            edges: vec![edge("in", "parse"), edge("parse", "out")],
+        };
+        let new = Topology {
+            nodes: vec![
+                node("in", ComponentKind::Source),
+                node("out", ComponentKind::Sink),
+                node("archive", ComponentKind::Sink),
+            ],
+            edges: vec![edge("in", "out"), edge("in", "archive")],
+        };
+        (old, new)
+    }
+
+    #[test]
+    fn text_lists_every_change() {
+        let (old, new) = fixtures();
+        let diff = TopologyDiff::new(&old, &new);
+
+        let mut output = Vec::new();
+        diff.write_text(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "- removed node parse\n\
+             + added node archive\n\
+             - removed edge in -> parse\n\
+             - removed edge parse -> out\n\
+             + added edge in -> out\n\
+             + added edge in -> archive\n"
+        );
+    }
+
+    #[test]
+    fn identical_topologies_have_no_diff() {
+        let (old, _) = fixtures();
+        let diff = TopologyDiff::new(&old, &old);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.overlay(&old).0, old);
+    }
+
+    #[test]
+    fn overlay_styles_changes() {
+        let (old, new) = fixtures();
+        let (topology, styles) = TopologyDiff::new(&old, &new).overlay(&new);
+
+        let ids = topology
+            .nodes
+            .iter()
+            .map(|node| node.id.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["in", "parse", "out", "archive"]);
+        assert_eq!(topology.edges.len(), 4);
+
+        assert_eq!(styles.nodes[&"archive".into()], Style::color(ADDED_COLOR));
+        assert_eq!(
+            styles.nodes[&"parse".into()],
+            Style::color(REMOVED_COLOR).dashed()
+        );
+        assert_eq!(styles.edges.len(), 4);
+        assert_eq!(styles.edges[&2], Style::color(REMOVED_COLOR).dashed());
+    }
+}
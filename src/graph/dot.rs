@@ -0,0 +1,305 @@
+//! Renders a topology in the [DOT language](https://graphviz.org/doc/info/lang.html).
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+
+use super::{ComponentKind, Edge, GraphOptions, Node, Style, Topology};
+use crate::config::ComponentKey;
+
+/// Writes the topology as a DOT digraph. Nodes are labeled with their display name if they have
+/// one, and with the file they were defined in if it's in the options' `origins`.
+pub fn write_dot<W: Write + ?Sized>(
+    topology: &Topology,
+    options: &GraphOptions,
+    w: &mut W,
+) -> io::Result<()> {
+    let prefix = options.id_prefix.as_deref().unwrap_or_default();
+    let id = |key| Id::new(prefix, key, options.quote_ids);
+
+    if options.strict_dot {
+        write!(w, "strict ")?;
+    }
+    writeln!(w, "digraph {{")?;
+    if let Some(color) = &options.bgcolor {
+        writeln!(w, "  bgcolor=\"{}\"", escape(color))?;
+    }
+    if options.align_io {
+        writeln!(w, "  rankdir=LR")?;
+    }
+
+    for node in &topology.nodes {
+        let mut attributes = Vec::new();
+        if options.html_labels {
+            // The table draws the node, so it needs no shape of its own.
+            attributes.push("shape=plain".to_owned());
+            attributes.push(format!("label=<{}>", html_label(node, options)));
+            if let Some(origin) = options.origins.get(&node.id) {
+                attributes.push(format!(
+                    "tooltip=\"{}\"",
+                    escape(&origin.display().to_string())
+                ));
+            }
+        } else {
+            attributes.push(format!("shape={}", shape(node.kind)));
+            let mut label = escape(node.label.as_deref().unwrap_or_else(|| node.id.id()));
+            if options.icons {
+                label = Cow::Owned(format!("{}{}", icon(node.kind), label));
+            }
+            if let Some(origin) = options.origins.get(&node.id) {
+                let origin = origin.display().to_string();
+                let origin = escape(&origin);
+                attributes.push(format!("label=\"{}\\n{}\"", label, origin));
+                attributes.push(format!("tooltip=\"{}\"", origin));
+            } else if node.label.is_some() || options.icons || !prefix.is_empty() {
+                // Without a label, GraphViz would show the prefixed ID.
+                attributes.push(format!("label=\"{}\"", label));
+            }
+        }
+        if let Some(style) = options.styles.nodes.get(&node.id) {
+            attributes.extend(style_attributes(style));
+        }
+
+        writeln!(w, "  {} [{}]", id(&node.id), attributes.join(", "))?;
+    }
+
+    if !options.clusters.is_empty() {
+        let drawn = topology
+            .nodes
+            .iter()
+            .map(|node| &node.id)
+            .collect::<HashSet<_>>();
+        // GraphViz only draws a box around subgraphs whose name starts with `cluster`.
+        for (index, cluster) in options.clusters.iter().enumerate() {
+            writeln!(w, "  subgraph cluster_{} {{", index)?;
+            writeln!(w, "    label=\"{}\"", escape(&cluster.name))?;
+            for member in cluster.members.iter().filter(|id| drawn.contains(id)) {
+                writeln!(w, "    {}", id(member))?;
+            }
+            writeln!(w, "  }}")?;
+        }
+    }
+
+    if options.align_io {
+        // Pinning the ends keeps a source the layout would place further along, such as one
+        // feeding a late transform, from breaking up the left-to-right reading order.
+        for (kind, rank) in [(ComponentKind::Source, "min"), (ComponentKind::Sink, "max")] {
+            let ids = topology
+                .nodes
+                .iter()
+                .filter(|node| node.kind == kind)
+                .map(|node| id(&node.id).to_string())
+                .collect::<Vec<_>>();
+            if !ids.is_empty() {
+                writeln!(w, "  {{ rank={}; {} }}", rank, ids.join("; "))?;
+            }
+        }
+    }
+
+    for (index, edge) in topology.edges.iter().enumerate() {
+        let mut attributes = Vec::new();
+        let label = options.edge_label(edge);
+        if let Some(label) = &label {
+            attributes.push(format!("label=\"{}\"", escape(label)));
+        }
+        if options.edge_tooltips {
+            let tooltip = edge_tooltip(edge, options);
+            let tooltip = escape(&tooltip);
+            attributes.push(format!("edgetooltip=\"{}\"", tooltip));
+            // The label is a separate element in SVG, with a tooltip of its own.
+            if label.is_some() {
+                attributes.push(format!("labeltooltip=\"{}\"", tooltip));
+            }
+        }
+        if let Some(style) = options.styles.edges.get(&index) {
+            attributes.extend(style_attributes(style));
+        }
+
+        write!(w, "  {} -> {}", id(&edge.source), id(&edge.target))?;
+        if !attributes.is_empty() {
+            write!(w, " [{}]", attributes.join(", "))?;
+        }
+        writeln!(w)?;
+    }
+
+    writeln!(w, "}}")
+}
+
+/// Returns the IDs in the topology that can't be written as bare DOT identifiers once `prefix`
+/// is prepended to them.
+pub fn unquotable_ids<'a>(topology: &'a Topology, prefix: &str) -> Vec<&'a ComponentKey> {
+    topology
+        .nodes
+        .iter()
+        .map(|node| &node.id)
+        .filter(|id| !is_bare_id(&format!("{}{}", prefix, id.id())))
+        .collect()
+}
+
+/// Whether `id` is a DOT identifier that doesn't need quoting: an alphabetic string that isn't a
+/// keyword, or a numeral.
+fn is_bare_id(id: &str) -> bool {
+    const KEYWORDS: &[&str] = &["node", "edge", "graph", "digraph", "subgraph", "strict"];
+
+    let alphabetic = id
+        .chars()
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(id));
+
+    let digits = id.strip_prefix('-').unwrap_or(id);
+    let numeral = !digits.is_empty()
+        && digits != "."
+        && digits.chars().filter(|c| *c == '.').count() <= 1
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.');
+
+    alphabetic || numeral
+}
+
+const fn shape(kind: ComponentKind) -> &'static str {
+    match kind {
+        ComponentKind::Source => "trapezium",
+        ComponentKind::Transform => "diamond",
+        ComponentKind::Sink => "invtrapezium",
+    }
+}
+
+/// The prefix `--icons` adds to labels. GraphViz fonts can't be relied on for emoji, so DOT
+/// spells the kind out instead.
+const fn icon(kind: ComponentKind) -> &'static str {
+    match kind {
+        ComponentKind::Source => "source: ",
+        ComponentKind::Transform => "transform: ",
+        ComponentKind::Sink => "sink: ",
+    }
+}
+
+/// The color of the title bar of an HTML label.
+const fn title_color(kind: ComponentKind) -> &'static str {
+    match kind {
+        ComponentKind::Source => "#4e79a7",
+        ComponentKind::Transform => "#f28e2b",
+        ComponentKind::Sink => "#59a14f",
+    }
+}
+
+/// An HTML-like label: a table with the node's name on a title bar colored by its kind, then a
+/// row for its type and one for its origin when they are known.
+fn html_label(node: &Node, options: &GraphOptions) -> String {
+    let mut name = html_escape(node.label.as_deref().unwrap_or_else(|| node.id.id())).into_owned();
+    if options.icons {
+        name.insert_str(0, icon(node.kind));
+    }
+
+    let mut rows = vec![format!(
+        "<tr><td bgcolor=\"{}\"><font color=\"white\"><b>{}</b></font></td></tr>",
+        title_color(node.kind),
+        name
+    )];
+    if let Some(component_type) = options.component_types.get(&node.id) {
+        rows.push(format!("<tr><td>{}</td></tr>", html_escape(component_type)));
+    }
+    if let Some(origin) = options.origins.get(&node.id) {
+        rows.push(format!(
+            "<tr><td><font point-size=\"10\">{}</font></td></tr>",
+            html_escape(&origin.display().to_string())
+        ));
+    }
+    format!(
+        "<table border=\"0\" cellborder=\"1\" cellspacing=\"0\" cellpadding=\"4\">{}</table>",
+        rows.concat()
+    )
+}
+
+/// The tooltip of an edge, such as `parse (remap) --errors--> out (console)`. Types are left out
+/// for components not in the options' `component_types`.
+fn edge_tooltip(edge: &Edge, options: &GraphOptions) -> String {
+    let component = |id: &ComponentKey| match options.component_types.get(id) {
+        Some(component_type) => format!("{} ({})", id, component_type),
+        None => id.to_string(),
+    };
+    let arrow = match &edge.port {
+        Some(port) => format!("--{}-->", port),
+        None => "-->".to_owned(),
+    };
+    format!(
+        "{} {} {}",
+        component(&edge.source),
+        arrow,
+        component(&edge.target)
+    )
+}
+
+/// Escapes a string for use as text in an HTML-like label.
+fn html_escape(value: &str) -> Cow<'_, str> {
+    if value.contains(['&', '<', '>', '"']) {
+        Cow::Owned(
+            value
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;"),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Renders the DOT attributes for a style.
+fn style_attributes(style: &Style) -> Vec<String> {
+    let mut attributes = Vec::new();
+    if let Some(color) = &style.color {
+        attributes.push(format!("color=\"{}\"", escape(color)));
+    }
+    if style.dashed {
+        attributes.push("style=dashed".to_owned());
+    }
+    if style.thick {
+        attributes.push("penwidth=3".to_owned());
+    }
+    if style.both_ends {
+        attributes.push("dir=both".to_owned());
+    }
+    attributes
+}
+
+/// Escapes a string for use inside a quoted DOT string, borrowing it when nothing needs
+/// escaping as is the case for almost every ID.
+fn escape(value: &str) -> Cow<'_, str> {
+    if value.contains(['\\', '"']) {
+        Cow::Owned(value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// A node ID as written in DOT, formatted in place so drawing an edge doesn't allocate.
+struct Id<'a> {
+    prefix: &'a str,
+    id: &'a str,
+    quoted: bool,
+}
+
+impl<'a> Id<'a> {
+    fn new(prefix: &'a str, key: &'a ComponentKey, quoted: bool) -> Self {
+        Self {
+            prefix,
+            id: key.id(),
+            quoted,
+        }
+    }
+}
+
+impl fmt::Display for Id<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quoted {
+            write!(f, "\"{}{}\"", escape(self.prefix), escape(self.id))
+        } else {
+            write!(f, "{}{}", self.prefix, self.id)
+        }
+    }
+}
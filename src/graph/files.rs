@@ -0,0 +1,224 @@
+//! Lists the files `vector` reads from a set of config paths, following the loader's directory
+//! rules, for the analyses that read config files as text or watch them for changes.
+//!
+//! A config directory is loaded from the TOML, YAML, and JSON files directly in it, each a whole
+//! config, and from its component subdirectories, such as `sinks`, where each file defines the
+//! component named by its file stem. Only `transforms` is walked further: a subdirectory of it
+//! holds parts of the transform it is named after. Hidden subdirectories are never walked.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::ComponentKind;
+use crate::config::{ComponentKey, ConfigPath, Format};
+
+/// The subdirectories of a config directory whose files define components drawn in the graph.
+const COMPONENT_DIRS: &[(&str, ComponentKind)] = &[
+    ("sources", ComponentKind::Source),
+    ("transforms", ComponentKind::Transform),
+    ("sinks", ComponentKind::Sink),
+];
+
+/// The subdirectories of a config directory whose files define things that aren't drawn.
+const OTHER_DIRS: &[&str] = &["tests", "enrichment_tables"];
+
+/// What a config file defines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Defines {
+    /// A whole config, such as a file given with `--config` or one at the top of a config
+    /// directory.
+    Config,
+
+    /// A single component, in one of a config directory's component subdirectories.
+    Component(ComponentKind, ComponentKey),
+
+    /// A unit test or an enrichment table.
+    Other,
+}
+
+/// A config file `vector` loads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigFile {
+    /// The path of the file.
+    pub path: PathBuf,
+
+    /// The format the loader parses the file as.
+    pub format: Format,
+
+    /// What the file defines.
+    pub defines: Defines,
+}
+
+/// Every config file loaded from `paths`, in the order the loader reads them: each file given
+/// directly, and for each directory, the files at its top before those in its subdirectories.
+pub fn config_files(paths: &[ConfigPath]) -> Vec<ConfigFile> {
+    walk(paths).1
+}
+
+/// Every directory the loader reads from `paths`, so that files added to one can be noticed.
+pub fn config_dirs(paths: &[ConfigPath]) -> Vec<PathBuf> {
+    walk(paths).0
+}
+
+fn walk(paths: &[ConfigPath]) -> (Vec<PathBuf>, Vec<ConfigFile>) {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for path in paths {
+        match path {
+            ConfigPath::File(path, hint) => files.push(ConfigFile {
+                path: path.clone(),
+                format: hint
+                    .or_else(|| Format::from_path(path).ok())
+                    .unwrap_or_default(),
+                defines: Defines::Config,
+            }),
+            ConfigPath::Dir(dir) => walk_dir(dir, &mut dirs, &mut files),
+        }
+    }
+    (dirs, files)
+}
+
+fn walk_dir(dir: &Path, dirs: &mut Vec<PathBuf>, files: &mut Vec<ConfigFile>) {
+    let (top, _) = entries(dir);
+    dirs.push(dir.to_owned());
+    files.extend(config_files_in(top, |_| Defines::Config));
+
+    let component_dirs = COMPONENT_DIRS
+        .iter()
+        .map(|&(name, kind)| (name, Some(kind)))
+        .chain(OTHER_DIRS.iter().map(|&name| (name, None)));
+    for (name, kind) in component_dirs {
+        let subdir = dir.join(name);
+        if !subdir.is_dir() {
+            continue;
+        }
+        dirs.push(subdir.clone());
+        let defines = |path: &Path| match (kind, component_id(path)) {
+            (Some(kind), Some(id)) => Defines::Component(kind, id),
+            _ => Defines::Other,
+        };
+
+        let (top, nested) = entries(&subdir);
+        files.extend(config_files_in(top, defines));
+        if kind != Some(ComponentKind::Transform) {
+            continue;
+        }
+
+        // Everything under `transforms/<id>/` is part of the transform `<id>`. An explicit stack
+        // rather than recursion, so a directory tree of any depth can't overflow the call stack.
+        for part_dir in nested {
+            let defines = defines(&part_dir);
+            let mut pending = vec![part_dir];
+            while let Some(next) = pending.pop() {
+                let (top, nested) = entries(&next);
+                dirs.push(next);
+                files.extend(config_files_in(top, |_| defines.clone()));
+                pending.extend(nested.into_iter().rev());
+            }
+        }
+    }
+}
+
+/// The files and the subdirectories that aren't hidden directly in `dir`, each sorted.
+fn entries(dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut entries = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let (dirs, files) = entries
+        .into_iter()
+        .partition::<Vec<_>, _>(|path| path.is_dir());
+    let dirs = dirs
+        .into_iter()
+        .filter(|dir| {
+            !dir.file_name()
+                .map_or(false, |name| name.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    (files, dirs)
+}
+
+/// The files among `paths` with a config format, which are the only ones the loader reads from a
+/// directory.
+fn config_files_in(
+    paths: Vec<PathBuf>,
+    defines: impl Fn(&Path) -> Defines,
+) -> impl Iterator<Item = ConfigFile> {
+    paths.into_iter().filter_map(move |path| {
+        let format = Format::from_path(&path).ok()?;
+        let defines = defines(&path);
+        Some(ConfigFile {
+            path,
+            format,
+            defines,
+        })
+    })
+}
+
+/// The component a file in a component subdirectory, or a subdirectory of `transforms`, is
+/// named after.
+fn component_id(path: &Path) -> Option<ComponentKey> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(ComponentKey::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_loaders_directory_rules() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        for path in [
+            "vector.toml",
+            "README.md",
+            "sinks/out.yaml",
+            "transforms/parse.toml",
+            "transforms/parse/route.toml",
+            "transforms/nested/deeper/part.json",
+            "transforms/.hidden/skipped.toml",
+            "tests/check.toml",
+            "unrelated/skipped.toml",
+        ] {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let files = config_files(&[ConfigPath::Dir(dir.to_owned())])
+            .into_iter()
+            .map(|file| {
+                (
+                    file.path.strip_prefix(dir).unwrap().to_owned(),
+                    file.defines,
+                )
+            })
+            .collect::<Vec<_>>();
+        let transform = |id: &str| Defines::Component(ComponentKind::Transform, id.into());
+        assert_eq!(
+            files,
+            [
+                (PathBuf::from("vector.toml"), Defines::Config),
+                (PathBuf::from("transforms/parse.toml"), transform("parse")),
+                (
+                    PathBuf::from("transforms/nested/deeper/part.json"),
+                    transform("nested")
+                ),
+                (
+                    PathBuf::from("transforms/parse/route.toml"),
+                    transform("parse")
+                ),
+                (
+                    PathBuf::from("sinks/out.yaml"),
+                    Defines::Component(ComponentKind::Sink, "out".into())
+                ),
+                (PathBuf::from("tests/check.toml"), Defines::Other),
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Renders DOT source to images using a GraphViz layout engine binary, such as `dot`.
+
+use std::fmt;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running layout engine is checked against its timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Why an image couldn't be rendered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenderError {
+    /// The layout engine was still running when the timeout passed, and was killed.
+    TimedOut(Duration),
+
+    /// Anything else, described for the user.
+    Failed(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut(timeout) => write!(
+                f,
+                "GraphViz was stopped after taking more than {}s to lay out the graph. \
+                 Use --filter to render a smaller part of it.",
+                timeout.as_secs_f64()
+            ),
+            Self::Failed(error) => f.write_str(error),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<String> for RenderError {
+    fn from(error: String) -> Self {
+        Self::Failed(error)
+    }
+}
+
+/// Streams the DOT source produced by `write_source` through `<engine> -T<format>`, returning
+/// the rendered image. The engine is killed if it runs for longer than `timeout`.
+pub fn render(
+    engine: &str,
+    format: &str,
+    timeout: Option<Duration>,
+    write_source: impl FnOnce(&mut dyn Write) -> io::Result<()> + Send,
+) -> Result<Vec<u8>, RenderError> {
+    let mut child = Command::new(engine)
+        .arg(format!("-T{}", format))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| {
+            format!(
+                "Failed to run `{}`, make sure GraphViz is installed with that layout engine: {}",
+                engine, error
+            )
+        })?;
+
+    // Feed the source and drain the output from separate threads so a large image can't fill
+    // a pipe while we are still writing, and so the engine can be killed if it hangs. The
+    // readers aren't scoped, since whatever it started may keep the pipes open after it is
+    // killed.
+    let stdin = child.stdin.take().expect("stdin should be piped");
+    let stdout = child.stdout.take().expect("stdout should be piped");
+    let stderr = child.stderr.take().expect("stderr should be piped");
+    let stdout = thread::spawn(move || read_all(stdout));
+    let stderr = thread::spawn(move || read_all(stderr));
+    let (status, written) = thread::scope(|scope| {
+        let writer = scope.spawn(move || {
+            let mut stdin = BufWriter::new(stdin);
+            write_source(&mut stdin).and_then(|_| stdin.flush())
+        });
+        let status = wait(engine, &mut child, timeout);
+        (
+            status,
+            writer.join().expect("writer thread should not panic"),
+        )
+    });
+    let status = status?;
+    let stdout = stdout.join().expect("reader thread should not panic");
+    let stderr = stderr.join().expect("reader thread should not panic");
+    let stdout = stdout.map_err(|error| format!("Failed to read from `{}`: {}", engine, error))?;
+
+    if !status.success() {
+        return Err(format!(
+            "`{}` exited with {}: {}",
+            engine,
+            status,
+            String::from_utf8_lossy(&stderr.unwrap_or_default()).trim()
+        )
+        .into());
+    }
+    written.map_err(|error| format!("Failed to write to `{}`: {}", engine, error))?;
+
+    Ok(stdout)
+}
+
+fn read_all(mut pipe: impl Read) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    pipe.read_to_end(&mut output)?;
+    Ok(output)
+}
+
+/// Waits for `child` to exit, killing it once `timeout` has passed.
+fn wait(
+    engine: &str,
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, RenderError> {
+    let waited = |error| RenderError::Failed(format!("Failed to wait for `{}`: {}", engine, error));
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return child.wait().map_err(waited),
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(waited)? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            // Reap the process too, so it isn't left behind as a zombie.
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(RenderError::TimedOut(timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,154 @@
+//! A lookup table of the components in a config and the edges between them, built once per
+//! command.
+
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use vector_config::NamedComponent;
+
+use super::ComponentKind;
+use crate::config::{ComponentKey, ConfigBuilder};
+
+/// What the index knows about a single component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexedComponent {
+    /// Whether the component is a source, transform, or sink.
+    pub kind: ComponentKind,
+
+    /// The component's `type`, such as `remap` or `http_server`.
+    pub component_type: &'static str,
+}
+
+/// Which way to follow edges from a component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Towards the components it reads from.
+    Upstream,
+
+    /// Towards the components that read from it.
+    Downstream,
+}
+
+/// Every component in a config by ID, in the order they were declared, along with the edges
+/// between them once [`connect`](Self::connect) has indexed them.
+///
+/// Building a config consumes the builder, so the index keeps what analyses need rather than
+/// borrowing the components themselves.
+///
+/// The edges are indexed once, before any filter narrows the topology down, so analyses of a
+/// narrowed topology are told which components it still has and only follow edges between those.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentIndex {
+    components: IndexMap<ComponentKey, IndexedComponent>,
+    inputs: HashMap<ComponentKey, Vec<ComponentKey>>,
+    outputs: HashMap<ComponentKey, Vec<ComponentKey>>,
+}
+
+impl ComponentIndex {
+    pub fn new(builder: &ConfigBuilder) -> Self {
+        let sources = builder.sources.iter().map(|(id, source)| {
+            let component_type = source.inner.get_component_name();
+            (id, ComponentKind::Source, component_type)
+        });
+        let transforms = builder.transforms.iter().map(|(id, transform)| {
+            let component_type = transform.inner.get_component_name();
+            (id, ComponentKind::Transform, component_type)
+        });
+        let sinks = builder.sinks.iter().map(|(id, sink)| {
+            let component_type = sink.inner.get_component_name();
+            (id, ComponentKind::Sink, component_type)
+        });
+
+        let components = sources
+            .chain(transforms)
+            .chain(sinks)
+            .map(|(id, kind, component_type)| {
+                let component = IndexedComponent {
+                    kind,
+                    component_type,
+                };
+                (id.clone(), component)
+            })
+            .collect();
+
+        Self {
+            components,
+            ..Self::default()
+        }
+    }
+
+    /// Indexes the `(source, target)` pairs of every edge, so analyses can follow edges from a
+    /// component without scanning all of them.
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn connect<'a>(
+        mut self,
+        edges: impl IntoIterator<Item = (&'a ComponentKey, &'a ComponentKey)>,
+    ) -> Self {
+        for (source, target) in edges {
+            self.inputs
+                .entry(target.clone())
+                .or_default()
+                .push(source.clone());
+            self.outputs
+                .entry(source.clone())
+                .or_default()
+                .push(target.clone());
+        }
+        self
+    }
+
+    /// The components `id` is connected to by a single edge in `direction`, once for each edge.
+    pub fn adjacent(&self, id: &ComponentKey, direction: Direction) -> &[ComponentKey] {
+        let adjacent = match direction {
+            Direction::Upstream => &self.inputs,
+            Direction::Downstream => &self.outputs,
+        };
+        adjacent.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The seeds along with every component reachable from them by following edges in
+    /// `direction`, only passing through components for which `within` is true.
+    pub fn reachable(
+        &self,
+        seeds: impl IntoIterator<Item = ComponentKey>,
+        direction: Direction,
+        within: impl Fn(&ComponentKey) -> bool,
+    ) -> HashSet<ComponentKey> {
+        let mut pending = seeds.into_iter().collect::<Vec<_>>();
+        let mut reached = pending.iter().cloned().collect::<HashSet<_>>();
+        while let Some(id) = pending.pop() {
+            for next in self.adjacent(&id, direction) {
+                if within(next) && reached.insert(next.clone()) {
+                    pending.push(next.clone());
+                }
+            }
+        }
+        reached
+    }
+
+    /// Looks up a component. Components Vector adds itself while building the config aren't
+    /// indexed.
+    pub fn get(&self, id: &ComponentKey) -> Option<IndexedComponent> {
+        self.components.get(id).copied()
+    }
+
+    /// Every indexed component, in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&ComponentKey, IndexedComponent)> {
+        self.components
+            .iter()
+            .map(|(id, component)| (id, *component))
+    }
+
+    /// The IDs of the components of `kind` whose type matches `predicate`, in declaration order.
+    pub fn matching(
+        &self,
+        kind: ComponentKind,
+        mut predicate: impl FnMut(&str) -> bool,
+    ) -> Vec<ComponentKey> {
+        self.components
+            .iter()
+            .filter(|(_, component)| component.kind == kind && predicate(component.component_type))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
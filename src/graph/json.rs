@@ -0,0 +1,158 @@
+//! Renders a topology as JSON, either as the [`Topology`] itself, as an adjacency map, or as
+//! JSON Lines with a line per node and edge.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::{ComponentKind, Edge, GraphOptions, JsonShape, Node, Topology};
+use crate::config::ComponentKey;
+
+/// Each component's successors, for consumers that only walk the graph downstream.
+#[derive(Serialize)]
+struct Adjacency<'a> {
+    /// The components each component sends events to, in edge order and without duplicates.
+    /// Every node has an entry, so sinks map to an empty list.
+    adjacency: IndexMap<&'a str, Vec<&'a str>>,
+
+    /// The kind of every component.
+    kinds: IndexMap<&'a str, ComponentKind>,
+}
+
+/// A node annotated with its layer, for `--with-layers`.
+#[derive(Serialize)]
+struct LayeredNode<'a> {
+    #[serde(flatten)]
+    node: &'a Node,
+
+    /// The node's layer, left out if the graph is cyclic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layer: Option<usize>,
+}
+
+/// The topology with each node annotated with its layer, for `--with-layers`.
+#[derive(Serialize)]
+struct LayeredGraph<'a> {
+    nodes: Vec<LayeredNode<'a>>,
+    edges: &'a [Edge],
+
+    /// Set instead of the layers when the graph has a cycle.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cyclic: bool,
+}
+
+/// Writes the topology in the options' JSON shape, followed by a newline. Graphs and adjacency
+/// maps are pretty-printed.
+pub fn write_json<W: Write + ?Sized>(
+    topology: &Topology,
+    options: &GraphOptions,
+    w: &mut W,
+) -> io::Result<()> {
+    let prefixed;
+    let topology = match &options.id_prefix {
+        Some(prefix) => {
+            prefixed = with_id_prefix(topology, prefix);
+            &prefixed
+        }
+        None => topology,
+    };
+
+    let layers = options.layers.then(|| topology.layers());
+    match (options.json_shape, layers) {
+        (JsonShape::Graph, Some(layers)) => {
+            let graph = LayeredGraph {
+                nodes: layered_nodes(topology, layers.as_ref()),
+                edges: &topology.edges,
+                cyclic: layers.is_none(),
+            };
+            serde_json::to_writer_pretty(&mut *w, &graph)?;
+        }
+        (JsonShape::Graph, None) => serde_json::to_writer_pretty(&mut *w, topology)?,
+        (JsonShape::Adjacency, _) => {
+            serde_json::to_writer_pretty(&mut *w, &adjacency(topology))?;
+        }
+        (JsonShape::PerLine, layers) => return write_lines(topology, layers.flatten().as_ref(), w),
+    }
+    writeln!(w)
+}
+
+fn layered_nodes<'a>(
+    topology: &'a Topology,
+    layers: Option<&HashMap<&ComponentKey, usize>>,
+) -> Vec<LayeredNode<'a>> {
+    topology
+        .nodes
+        .iter()
+        .map(|node| LayeredNode {
+            node,
+            layer: layers.and_then(|layers| layers.get(&node.id).copied()),
+        })
+        .collect()
+}
+
+/// Copies the topology with `prefix` prepended to every node ID, including those in edges.
+fn with_id_prefix(topology: &Topology, prefix: &str) -> Topology {
+    let prefixed = |id: &ComponentKey| ComponentKey::from(format!("{}{}", prefix, id.id()));
+    Topology {
+        nodes: topology
+            .nodes
+            .iter()
+            .map(|node| Node {
+                id: prefixed(&node.id),
+                ..node.clone()
+            })
+            .collect(),
+        edges: topology
+            .edges
+            .iter()
+            .map(|edge| Edge {
+                source: prefixed(&edge.source),
+                target: prefixed(&edge.target),
+                port: edge.port.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Writes every node and then every edge on a line of its own. Nodes are annotated with their
+/// layers if there are any.
+fn write_lines<W: Write + ?Sized>(
+    topology: &Topology,
+    layers: Option<&HashMap<&ComponentKey, usize>>,
+    w: &mut W,
+) -> io::Result<()> {
+    for node in layered_nodes(topology, layers) {
+        serde_json::to_writer(&mut *w, &node)?;
+        writeln!(w)?;
+    }
+    for edge in &topology.edges {
+        serde_json::to_writer(&mut *w, edge)?;
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+fn adjacency(topology: &Topology) -> Adjacency<'_> {
+    let mut adjacency = topology
+        .nodes
+        .iter()
+        .map(|node| (node.id.id(), Vec::new()))
+        .collect::<IndexMap<_, _>>();
+    for edge in &topology.edges {
+        let successors = adjacency.entry(edge.source.id()).or_default();
+        // Several ports of one component may feed the same target.
+        if !successors.contains(&edge.target.id()) {
+            successors.push(edge.target.id());
+        }
+    }
+
+    let kinds = topology
+        .nodes
+        .iter()
+        .map(|node| (node.id.id(), node.kind))
+        .collect();
+
+    Adjacency { adjacency, kinds }
+}
@@ -0,0 +1,49 @@
+//! Renders diagrams to images using a [Kroki](https://kroki.io) server.
+
+use hyper::Body;
+use url::Url;
+
+use crate::{config::ProxyConfig, http::HttpClient};
+
+/// Posts `source` to the Kroki server at `url`, returning the diagram rendered as `format`.
+///
+/// `diagram_type` is Kroki's name for the source language, such as `graphviz` or `mermaid`.
+pub async fn render(
+    url: &Url,
+    diagram_type: &str,
+    format: &str,
+    source: Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let endpoint = format!(
+        "{}/{}/{}",
+        url.as_str().trim_end_matches('/'),
+        diagram_type,
+        format
+    );
+
+    let client = HttpClient::<Body>::new(None, &ProxyConfig::from_env())
+        .map_err(|error| format!("Failed to create HTTP client: {}", error))?;
+    let request = http::Request::post(&endpoint)
+        .header(http::header::CONTENT_TYPE, "text/plain")
+        .body(Body::from(source))
+        .map_err(|error| format!("Invalid Kroki URL {:?}: {}", endpoint, error))?;
+
+    let response = client
+        .send(request)
+        .await
+        .map_err(|error| format!("Failed to reach Kroki at {:?}: {}", endpoint, error))?;
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(|error| format!("Failed to read the response from Kroki: {}", error))?;
+
+    if !status.is_success() {
+        return Err(format!(
+            "Kroki responded with {}: {}",
+            status,
+            String::from_utf8_lossy(&body).trim()
+        ));
+    }
+
+    Ok(body.to_vec())
+}
@@ -0,0 +1,306 @@
+//! Reads display names for components from `# label: ...` comments in config files.
+//!
+//! Comments aren't part of the parsed config, so the files are scanned as text: a label comment
+//! applies to the component declared on the next line that isn't a comment or blank. Components
+//! defined in their own file in a config directory can also be labeled by a comment at the top
+//! of the file. JSON has no comments, so JSON configs are never labeled.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::files::{config_files, Defines};
+use crate::config::{ComponentKey, ConfigPath, Format};
+
+const COMPONENT_FIELDS: &[&str] = &["sources", "transforms", "sinks"];
+
+/// Collects the label comments in every config file the paths refer to.
+pub fn label_comments(paths: &[ConfigPath]) -> HashMap<ComponentKey, String> {
+    let mut labels = HashMap::new();
+
+    for file in config_files(paths) {
+        match file.defines {
+            Defines::Config => labels.extend(labels_in_file(&file.path, file.format)),
+            // Parts nested under `transforms/<id>/` don't label the transform.
+            Defines::Component(_, id) if file.path.file_stem() == Some(id.id().as_ref()) => {
+                let label = fs::read_to_string(&file.path)
+                    .ok()
+                    .and_then(|text| leading_label(&text));
+                if let Some(label) = label {
+                    labels.insert(id, label);
+                }
+            }
+            Defines::Component(..) | Defines::Other => {}
+        }
+    }
+
+    labels
+}
+
+fn labels_in_file(path: &Path, format: Format) -> Vec<(ComponentKey, String)> {
+    fs::read_to_string(path)
+        .map(|text| match format {
+            Format::Toml => labels_in_toml(&text),
+            Format::Yaml => labels_in_yaml(&text),
+            Format::Json => Vec::new(),
+        })
+        .unwrap_or_default()
+}
+
+/// Labels components declared with `[<field>.<id>]` table headers.
+fn labels_in_toml(text: &str) -> Vec<(ComponentKey, String)> {
+    let mut labels = Vec::new();
+    let mut pending = None;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            if let Some(label) = label(line) {
+                pending = Some(label);
+            }
+            continue;
+        }
+
+        let label = pending.take();
+        let key = line
+            .strip_prefix('[')
+            .filter(|header| !header.starts_with('['))
+            .and_then(|header| header.split_once(']'))
+            .and_then(|(header, _)| toml_key(header));
+        if let (Some(label), Some([field, id])) = (label, key.as_deref()) {
+            if COMPONENT_FIELDS.contains(&field.as_str()) {
+                labels.push((id.as_str().into(), label));
+            }
+        }
+    }
+
+    labels
+}
+
+/// Splits a dotted TOML key into its segments, unquoting any quoted ones. Returns `None` for keys
+/// that aren't valid.
+fn toml_key(key: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut chars = key.trim().chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut segment = String::new();
+        match chars.next()? {
+            '"' => loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => segment.push(chars.next()?),
+                    c => segment.push(c),
+                }
+            },
+            '\'' => loop {
+                match chars.next()? {
+                    '\'' => break,
+                    c => segment.push(c),
+                }
+            },
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' => {
+                segment.push(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                {
+                    segment.push(c);
+                }
+            }
+            _ => return None,
+        }
+        segments.push(segment);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some('.') => continue,
+            Some(_) => return None,
+            None => return Some(segments),
+        }
+    }
+}
+
+/// Labels components declared as keys one level below a top-level `<field>:` key.
+fn labels_in_yaml(text: &str) -> Vec<(ComponentKey, String)> {
+    let mut labels = Vec::new();
+    let mut pending = None;
+    let mut in_components = false;
+    let mut component_indent = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            if let Some(label) = label(trimmed) {
+                pending = Some(label);
+            }
+            continue;
+        }
+
+        let label = pending.take();
+        let indent = line.len() - line.trim_start().len();
+        let key = trimmed
+            .split_once(':')
+            .map(|(key, _)| key.trim().trim_matches(|c| c == '"' || c == '\''));
+
+        if indent == 0 {
+            in_components = key.map_or(false, |key| COMPONENT_FIELDS.contains(&key));
+            component_indent = None;
+            continue;
+        }
+        if !in_components {
+            continue;
+        }
+        if *component_indent.get_or_insert(indent) == indent {
+            if let (Some(label), Some(key)) = (label, key) {
+                labels.push((key.into(), label));
+            }
+        }
+    }
+
+    labels
+}
+
+/// The label in the comments at the very top of a file, if any.
+fn leading_label(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .filter_map(label)
+        .last()
+}
+
+/// Parses a `# label: <name>` comment.
+fn label(comment: &str) -> Option<String> {
+    comment
+        .strip_prefix('#')?
+        .trim_start()
+        .strip_prefix("label:")
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+/// Makes a label safe to draw in any format: line breaks and other control characters, Unicode
+/// line separators, and escape sequences such as `\n` become single spaces, double quotes
+/// become single quotes, backticks are dropped, and braces become parentheses.
+pub fn sanitize(label: &str) -> String {
+    let mut words = String::with_capacity(label.len());
+    let mut chars = label.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.next_if(|c| matches!(c, 'n' | 'r' | 't')).is_some() => {
+                words.push(' ');
+            }
+            '\u{2028}' | '\u{2029}' => words.push(' '),
+            '"' => words.push('\''),
+            '`' => {}
+            '{' => words.push('('),
+            '}' => words.push(')'),
+            c if c.is_control() => words.push(' '),
+            c => words.push(c),
+        }
+    }
+    words.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn labels(found: Vec<(ComponentKey, String)>) -> Vec<(String, String)> {
+        found
+            .into_iter()
+            .map(|(id, label)| (id.id().to_owned(), label))
+            .collect()
+    }
+
+    #[test]
+    fn toml_labels() {
+        let text = indoc! {r#"
+            # label: Ingest API
+            [sources.in]
+            type = "http_server"
+
+            # label: Not a component
+            [api]
+            enabled = true
+
+            # Parses the body.
+            # label: Parse
+            [transforms."parse logs"]
+            type = "remap"
+
+            # label: Nested table
+            [transforms."parse logs".options]
+
+            [sinks.out]
+            type = "console"
+        "#};
+
+        assert_eq!(
+            labels(labels_in_toml(text)),
+            [
+                ("in".to_owned(), "Ingest API".to_owned()),
+                ("parse logs".to_owned(), "Parse".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn yaml_labels() {
+        let text = indoc! {r#"
+            # label: Not a component
+            sources:
+              # label: Ingest API
+              in:
+                type: http_server
+                # label: Not a component either
+                decoding:
+                  codec: json
+              # label: Metrics
+              "host metrics":
+                type: host_metrics
+            sinks:
+              out:
+                type: console
+        "#};
+
+        assert_eq!(
+            labels(labels_in_yaml(text)),
+            [
+                ("in".to_owned(), "Ingest API".to_owned()),
+                ("host metrics".to_owned(), "Metrics".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitized_labels() {
+        assert_eq!(
+            sanitize("Parse \"raw\" {json}\r\tlogs\\nand\u{2028}`metrics`  "),
+            "Parse 'raw' (json) logs and metrics"
+        );
+        assert_eq!(sanitize("C:\\logs"), "C:\\logs");
+    }
+
+    #[test]
+    fn leading_labels() {
+        assert_eq!(
+            leading_label("# label: Archive\n\ntype = \"aws_s3\"\n# label: Too late\n"),
+            Some("Archive".to_owned())
+        );
+        assert_eq!(
+            leading_label("type = \"aws_s3\"\n# label: Too late\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Renders a topology as a [Mermaid flowchart](https://mermaid.js.org/syntax/flowchart.html) or
+//! [class diagram](https://mermaid.js.org/syntax/classDiagram.html).
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use super::{ComponentKind, GraphOptions, Style, Topology};
+use crate::config::ComponentKey;
+
+/// Writes the topology as a Mermaid flowchart. Nodes are labeled with their display name if they
+/// have one, and with the file they were defined in if it's in the options' `origins`.
+///
+/// Component IDs may contain characters Mermaid doesn't accept in node IDs, so every node is
+/// declared under an alias and labeled with its real ID.
+pub fn write_mermaid<W: Write + ?Sized>(
+    topology: &Topology,
+    options: &GraphOptions,
+    w: &mut W,
+) -> io::Result<()> {
+    let aliases = aliases(topology, options.id_prefix.as_deref().unwrap_or_default());
+    let alias = |id| alias(&aliases, id);
+
+    if let Some(init) = &options.mermaid_init {
+        writeln!(w, "%%{{init: {}}}%%", init)?;
+    }
+    writeln!(w, "flowchart TD")?;
+
+    for node in &topology.nodes {
+        let mut label = escape(node.label.as_deref().unwrap_or_else(|| node.id.id()));
+        if options.icons {
+            label = Cow::Owned(format!("{} {}", icon(node.kind), label));
+        }
+        if let Some(origin) = options.origins.get(&node.id) {
+            let label = label.to_mut();
+            label.push_str("<br/>");
+            label.push_str(&escape(&origin.display().to_string()));
+        }
+
+        let (open, close) = shape(node.kind);
+        writeln!(w, "  {}{}\"{}\"{}", alias(&node.id), open, label, close)?;
+    }
+
+    if !options.clusters.is_empty() {
+        let taken = aliases
+            .values()
+            .map(|alias| alias.as_ref())
+            .collect::<HashSet<_>>();
+        for (index, cluster) in options.clusters.iter().enumerate() {
+            // Subgraph IDs share a namespace with node IDs.
+            let mut name = format!("cluster_{}", index);
+            while taken.contains(name.as_str()) {
+                name.push('_');
+            }
+
+            writeln!(w, "  subgraph {}[\"{}\"]", name, escape(&cluster.name))?;
+            for member in &cluster.members {
+                if let Some(alias) = aliases.get(member) {
+                    writeln!(w, "    {}", alias)?;
+                }
+            }
+            writeln!(w, "  end")?;
+        }
+    }
+
+    for (index, edge) in topology.edges.iter().enumerate() {
+        let (source, target) = (alias(&edge.source), alias(&edge.target));
+        let both_ends = options
+            .styles
+            .edges
+            .get(&index)
+            .map_or(false, |style| style.both_ends);
+        let arrow = if both_ends { "<-->" } else { "-->" };
+        if let Some(label) = options.edge_label(edge) {
+            writeln!(
+                w,
+                "  {} {}|\"{}\"| {}",
+                source,
+                arrow,
+                escape(&label),
+                target
+            )?;
+        } else {
+            writeln!(w, "  {} {} {}", source, arrow, target)?;
+        }
+    }
+
+    for node in &topology.nodes {
+        if let Some(css) = options
+            .styles
+            .nodes
+            .get(&node.id)
+            .map(css)
+            .filter(|css| !css.is_empty())
+        {
+            writeln!(w, "  style {} {}", alias(&node.id), css)?;
+        }
+    }
+
+    // Mermaid styles links by their position in the flowchart, which matches the edge index.
+    // Edges sharing a style are grouped into one statement.
+    let mut indices = options.styles.edges.keys().copied().collect::<Vec<_>>();
+    indices.sort_unstable();
+    let mut groups = Vec::<(&Style, Vec<String>)>::new();
+    for index in indices {
+        let style = &options.styles.edges[&index];
+        match groups.iter_mut().find(|(group, _)| *group == style) {
+            Some((_, members)) => members.push(index.to_string()),
+            None => groups.push((style, vec![index.to_string()])),
+        }
+    }
+    for (style, members) in groups {
+        let css = css(style);
+        if !css.is_empty() {
+            writeln!(w, "  linkStyle {} {}", members.join(","), css)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the topology as a Mermaid class diagram, with a class per component listing its kind
+/// and the settings in the options' `settings` as members, and an association per edge.
+///
+/// Classes are declared under the same aliases as flowchart nodes, and labeled with their real
+/// ID or display name when that differs.
+pub fn write_mermaid_class<W: Write + ?Sized>(
+    topology: &Topology,
+    options: &GraphOptions,
+    w: &mut W,
+) -> io::Result<()> {
+    let aliases = aliases(topology, options.id_prefix.as_deref().unwrap_or_default());
+    let alias = |id| alias(&aliases, id);
+
+    if let Some(init) = &options.mermaid_init {
+        writeln!(w, "%%{{init: {}}}%%", init)?;
+    }
+    writeln!(w, "classDiagram")?;
+
+    for node in &topology.nodes {
+        let name = alias(&node.id);
+        let label = node.label.as_deref().unwrap_or_else(|| node.id.id());
+        if label == name {
+            writeln!(w, "  class {} {{", name)?;
+        } else {
+            writeln!(w, "  class {}[\"{}\"] {{", name, escape(label))?;
+        }
+
+        writeln!(w, "    <<{}>>", annotation(node.kind))?;
+        for (key, value) in options.settings.get(&node.id).into_iter().flatten() {
+            writeln!(w, "    {}: {}", key, member(value))?;
+        }
+        writeln!(w, "  }}")?;
+    }
+
+    for edge in &topology.edges {
+        let (source, target) = (alias(&edge.source), alias(&edge.target));
+        if let Some(label) = options.edge_label(edge) {
+            writeln!(w, "  {} --> {} : {}", source, target, member(&label))?;
+        } else {
+            writeln!(w, "  {} --> {}", source, target)?;
+        }
+    }
+
+    Ok(())
+}
+
+const fn annotation(kind: ComponentKind) -> &'static str {
+    match kind {
+        ComponentKind::Source => "source",
+        ComponentKind::Transform => "transform",
+        ComponentKind::Sink => "sink",
+    }
+}
+
+/// Shortens a setting to one line that can't close the class body it is written in.
+fn member(value: &str) -> String {
+    const MAX_CHARS: usize = 60;
+
+    let line = value.lines().next().unwrap_or_default();
+    let mut member = line
+        .chars()
+        .map(|c| match c {
+            '{' | '}' => '_',
+            c => c,
+        })
+        .take(MAX_CHARS)
+        .collect::<String>();
+    if member.len() < value.len() {
+        member.push('\u{2026}');
+    }
+    escape(&member).into_owned()
+}
+
+const fn shape(kind: ComponentKind) -> (&'static str, &'static str) {
+    match kind {
+        ComponentKind::Source => ("[/", "\\]"),
+        ComponentKind::Transform => ("{", "}"),
+        ComponentKind::Sink => ("[\\", "/]"),
+    }
+}
+
+/// The emoji `--icons` adds to labels. Only labels change, never the node aliases.
+const fn icon(kind: ComponentKind) -> &'static str {
+    match kind {
+        ComponentKind::Source => "\u{1f4e5}",
+        ComponentKind::Transform => "\u{2699}\u{fe0f}",
+        ComponentKind::Sink => "\u{1f4e4}",
+    }
+}
+
+/// Assigns each node a Mermaid-safe ID starting with `prefix`. IDs that are already safe are
+/// used as-is, others have their invalid characters replaced, with a numeric suffix added if that
+/// causes a collision.
+fn aliases<'a>(topology: &'a Topology, prefix: &str) -> HashMap<&'a ComponentKey, Cow<'a, str>> {
+    let mut taken = HashSet::with_capacity(topology.nodes.len());
+    let mut aliases = HashMap::with_capacity(topology.nodes.len());
+    let safe = |id: &str| id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    for node in &topology.nodes {
+        let id = node.id.id();
+        let base = if prefix.is_empty() && safe(id) {
+            Cow::Borrowed(id)
+        } else {
+            prefix
+                .chars()
+                .chain(id.chars())
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect()
+        };
+
+        // `end` is a keyword that would close the flowchart.
+        let mut alias = base.clone();
+        let mut suffix = 1;
+        while alias.eq_ignore_ascii_case("end") || taken.contains(&alias) {
+            alias = Cow::Owned(format!("{}_{}", base, suffix));
+            suffix += 1;
+        }
+
+        taken.insert(alias.clone());
+        aliases.insert(&node.id, alias);
+    }
+
+    aliases
+}
+
+fn alias<'a>(aliases: &'a HashMap<&ComponentKey, Cow<'_, str>>, id: &'a ComponentKey) -> &'a str {
+    aliases.get(id).map_or(id.id(), |alias| alias)
+}
+
+/// Renders a style as the CSS properties Mermaid's `style` and `linkStyle` statements take.
+fn css(style: &Style) -> String {
+    let mut properties = Vec::new();
+    if let Some(color) = &style.color {
+        properties.push(format!("stroke:{}", color));
+    }
+    if style.dashed {
+        properties.push("stroke-dasharray:5 5".to_owned());
+    }
+    if style.thick {
+        properties.push("stroke-width:3px".to_owned());
+    }
+    properties.join(",")
+}
+
+/// Escapes a string for use inside a quoted Mermaid label, borrowing it when nothing needs
+/// escaping.
+fn escape(value: &str) -> Cow<'_, str> {
+    if value.contains('"') {
+        Cow::Owned(value.replace('"', "#quot;"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
@@ -0,0 +1,2759 @@
+#![allow(missing_docs)]
+mod bundle;
+mod changed;
+mod clipboard;
+mod cluster;
+mod describe;
+mod diff;
+mod dot;
+mod files;
+mod graphviz;
+mod index;
+mod json;
+mod kroki;
+mod labels;
+mod mermaid;
+mod options;
+mod report;
+mod style;
+#[cfg(test)]
+mod tests;
+mod topology;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use vector_buffers::BufferType;
+
+pub use self::cluster::Cluster;
+pub use self::diff::TopologyDiff;
+pub use self::dot::{unquotable_ids, write_dot};
+pub use self::graphviz::RenderError;
+pub use self::index::{ComponentIndex, Direction, IndexedComponent};
+pub use self::mermaid::write_mermaid;
+pub use self::options::GraphOptions;
+pub use self::style::{type_color, Palette, Style, Styles, PALETTE};
+pub use self::topology::{edges, edges_iter, ComponentKind, Edge, EdgeRef, Node, Topology};
+use crate::config::{self, ComponentKey, SinkConfig};
+
+/// The representation `vector graph` renders the topology as.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// The DOT language, which can be rendered using GraphViz.
+    Dot,
+
+    /// A Mermaid flowchart, which can be embedded in Markdown on GitHub and most wikis.
+    Mermaid,
+
+    /// A Mermaid class diagram, with each component's type and settings as members of its class.
+    MermaidClass,
+
+    /// A JSON document listing the nodes and edges of the topology.
+    Json,
+
+    /// An SVG image, rendered with GraphViz. See `--render-engine`.
+    Svg,
+
+    /// A PNG image, rendered with GraphViz. See `--render-engine`.
+    Png,
+}
+
+impl OutputFormat {
+    /// The file extension used when writing this format to `--output-dir`.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Mermaid => "mmd",
+            Self::MermaidClass => "class.mmd",
+            Self::Json => "json",
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+
+    /// Whether the format is an image rendered from the DOT source.
+    pub const fn is_image(self) -> bool {
+        matches!(self, Self::Svg | Self::Png)
+    }
+
+    /// The media type to serve this format as over HTTP.
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Dot => "text/vnd.graphviz",
+            Self::Mermaid | Self::MermaidClass => "text/plain; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Svg => "image/svg+xml",
+            Self::Png => "image/png",
+        }
+    }
+}
+
+/// Renders the topology in `format`, for callers other than the CLI such as the API.
+///
+/// Images are rendered with the GraphViz `dot` binary, which blocks the calling thread until it
+/// exits. Async callers such as a request handler should call this inside
+/// `tokio::task::spawn_blocking` and pass a `timeout`, after which `dot` is killed and the render
+/// fails with an error of kind [`io::ErrorKind::TimedOut`]. Without one, a `dot` that hangs blocks
+/// the thread forever. If `dot` can't be run, the error is of kind [`io::ErrorKind::Other`].
+pub fn render(
+    topology: &Topology,
+    format: OutputFormat,
+    options: &GraphOptions,
+    timeout: Option<Duration>,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    if format.is_image() {
+        let image = graphviz::render(
+            RenderEngine::Dot.binary(),
+            format.extension(),
+            timeout,
+            |w| dot::write_dot(topology, options, w),
+        )
+        .map_err(|error| {
+            let kind = match error {
+                RenderError::TimedOut(_) => io::ErrorKind::TimedOut,
+                RenderError::Failed(_) => io::ErrorKind::Other,
+            };
+            io::Error::new(kind, error)
+        })?;
+        w.write_all(&image)
+    } else {
+        write_text(format, topology, options, w)
+    }
+}
+
+/// The GraphViz layout engine that renders `svg` and `png` images.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderEngine {
+    /// Hierarchical layers, following the direction of the edges.
+    Dot,
+
+    /// Spring model layout, for small undirected-looking graphs.
+    Neato,
+
+    /// Force-directed layout.
+    Fdp,
+
+    /// Force-directed layout that scales to very large graphs.
+    Sfdp,
+
+    /// Circular layout.
+    Circo,
+}
+
+impl RenderEngine {
+    /// The name of the engine's binary.
+    pub const fn binary(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Neato => "neato",
+            Self::Fdp => "fdp",
+            Self::Sfdp => "sfdp",
+            Self::Circo => "circo",
+        }
+    }
+}
+
+/// The format of a `--report`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// One JSON document with the metrics and a list of findings.
+    Json,
+}
+
+/// How `--compare-to` reports the differences between two configs.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Render both topologies merged into one graph, with additions in green and removals in
+    /// dashed red.
+    Graph,
+
+    /// Print one line per added or removed node and edge.
+    Text,
+}
+
+/// How the `json` format lays out the topology.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonShape {
+    /// Arrays of every node and edge, which is the serialized [`Topology`].
+    Graph,
+
+    /// A map from each component ID to the IDs it sends events to, with a separate map of each
+    /// component's kind.
+    Adjacency,
+
+    /// Each node, then each edge, as a compact JSON object on a line of its own, so a
+    /// component's entries can be found with `grep`. The output is valid JSON Lines.
+    PerLine,
+}
+
+/// What `--cluster-by` groups components by.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterBy {
+    /// The start of the component ID, up to the first `_`, `-`, or `.`, such as `nginx` for
+    /// `nginx_logs`.
+    Prefix,
+
+    /// The component's type, such as `remap` or `http_server`.
+    Type,
+}
+
+/// The text format an image is rendered from by Kroki.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KrokiSource {
+    /// Render the DOT output with GraphViz.
+    Dot,
+
+    /// Render the Mermaid output with Mermaid.
+    Mermaid,
+}
+
+impl KrokiSource {
+    const fn format(self) -> OutputFormat {
+        match self {
+            Self::Dot => OutputFormat::Dot,
+            Self::Mermaid => OutputFormat::Mermaid,
+        }
+    }
+
+    /// Kroki's name for the diagram language.
+    const fn diagram_type(self) -> &'static str {
+        match self {
+            Self::Dot => "graphviz",
+            Self::Mermaid => "mermaid",
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(rename_all = "kebab-case")]
+pub struct Opts {
+    /// Read configuration from one or more files. Wildcard paths are supported.
+    /// File format is detected from the file name, unless the path ends in `:toml`, `:json`, or
+    /// `:yaml`, as in `vector.conf:toml`.
+    /// If zero files are specified the default config path
+    /// `/etc/vector/vector.toml` will be targeted.
+    #[arg(
+        id = "config",
+        short,
+        long,
+        env = "VECTOR_CONFIG",
+        value_delimiter(',')
+    )]
+    paths: Vec<PathBuf>,
+
+    /// Vector config files in TOML format.
+    #[arg(id = "config-toml", long, value_delimiter(','))]
+    paths_toml: Vec<PathBuf>,
+
+    /// Vector config files in JSON format.
+    #[arg(id = "config-json", long, value_delimiter(','))]
+    paths_json: Vec<PathBuf>,
+
+    /// Vector config files in YAML format.
+    #[arg(id = "config-yaml", long, value_delimiter(','))]
+    paths_yaml: Vec<PathBuf>,
+
+    /// Read a Vector config in TOML format from stdin, along with any config files given.
+    #[arg(long, group = "config-stdin", conflicts_with = "watch")]
+    config_stdin_toml: bool,
+
+    /// Read a Vector config in JSON format from stdin, along with any config files given.
+    #[arg(long, group = "config-stdin", conflicts_with = "watch")]
+    config_stdin_json: bool,
+
+    /// Read a Vector config in YAML format from stdin, along with any config files given.
+    #[arg(long, group = "config-stdin", conflicts_with = "watch")]
+    config_stdin_yaml: bool,
+
+    /// Read configuration from files in one or more directories.
+    /// File format is detected from the file name.
+    ///
+    /// Files not ending in .toml, .json, .yaml, or .yml will be ignored.
+    #[arg(
+        id = "config-dir",
+        short = 'C',
+        long,
+        env = "VECTOR_CONFIG_DIR",
+        value_delimiter(',')
+    )]
+    pub config_dirs: Vec<PathBuf>,
+
+    /// The formats to render the topology in.
+    ///
+    /// More than one can be given, comma separated or by repeating the flag, to write each of
+    /// them to `--output-dir` from a single config load.
+    #[arg(
+        id = "format",
+        short,
+        long = "format",
+        default_value = "dot",
+        value_delimiter(',')
+    )]
+    pub formats: Vec<OutputFormat>,
+
+    /// Render an empty graph and exit successfully when the config has no components at all,
+    /// such as a config directory with no files in it yet. This is the default.
+    #[arg(long, overrides_with = "fail_on_empty")]
+    pub exit_zero_on_empty: bool,
+
+    /// Exit with an error instead of rendering when the config has no components at all.
+    #[arg(long)]
+    pub fail_on_empty: bool,
+
+    /// Fail with a usage error unless `--format` is given, rather than rendering `dot` by
+    /// default, for scripts that should always say what they render.
+    #[arg(long, requires = "format")]
+    pub require_format: bool,
+
+    /// Render both DOT and Mermaid from a single config load, written one after the other
+    /// under `=== DOT ===` and `=== MERMAID ===` markers, for keeping the same diagram in two
+    /// tools.
+    #[arg(long, conflicts_with_all = ["format", "output_dir", "bundle"])]
+    pub render_both: bool,
+
+    /// Write the rendered graph to a file instead of stdout.
+    #[arg(short, long, conflicts_with = "output_dir")]
+    pub output: Option<PathBuf>,
+
+    /// Copy the rendered graph to the system clipboard instead of writing it to stdout. If no
+    /// clipboard is available, such as on a headless system, it is written to stdout anyway.
+    #[arg(long, conflicts_with_all = ["output", "output_dir", "bundle"])]
+    pub clipboard: bool,
+
+    /// Write the rendered graph to `graph.<format>` in a directory instead of stdout.
+    ///
+    /// Images rendered with GraphViz are accompanied by their DOT source in `graph.gv`, so the
+    /// image can always be regenerated.
+    ///
+    /// The directory is created if it doesn't exist, and checked to be writable before the
+    /// config is loaded.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Annotate each component with the config file it was defined in.
+    ///
+    /// The file is added to the node label and tooltip. Components loaded from a
+    /// config directory are attributed to their namespaced file when one exists,
+    /// otherwise to the directory itself.
+    #[arg(long)]
+    pub show_origin: bool,
+
+    /// Print every component in the merged config to stderr, with the file that defined it, so
+    /// the result of combining several files and directories can be checked before rendering.
+    #[arg(long)]
+    pub print_merged_components: bool,
+
+    /// Show origins relative to the directory all of the config files are in, such as
+    /// `services/nginx.toml` rather than `/etc/vector/conf.d/services/nginx.toml`.
+    #[arg(long, requires = "show_origin")]
+    pub strip_config_dir_prefix: bool,
+
+    /// Only render sinks that send data off the host, along with every component upstream
+    /// of them.
+    ///
+    /// Local sinks such as `console`, `file` or `blackhole` are left out. Any other sink type
+    /// is considered external.
+    #[arg(long)]
+    pub only_external_sinks: bool,
+
+    /// Only render the sinks of these types, such as `kafka`, along with every component
+    /// upstream of them.
+    #[arg(long, value_name = "TYPE", value_delimiter(','))]
+    pub reaching_sink_type: Vec<String>,
+
+    /// Only render the components that events from at least one source can reach, dropping
+    /// islands with no source upstream of them, such as transforms reading only each other.
+    #[arg(long)]
+    pub only_reachable_from_sources: bool,
+
+    /// Only render the components connected to at least this many others, counting both inputs
+    /// and outputs, along with the edges between them, to show the backbone of a large
+    /// pipeline. Degrees are counted after the other filters.
+    ///
+    /// Warns if the components left aren't all connected to each other.
+    #[arg(long, value_name = "N")]
+    pub min_degree: Option<usize>,
+
+    /// Write DOT node IDs without quotes, for tools that can't handle quoted IDs.
+    ///
+    /// This fails if any component ID isn't a valid bare DOT identifier.
+    #[arg(long)]
+    pub no_quote_ids: bool,
+
+    /// Prepend this to every node ID in DOT, Mermaid, and JSON output, including in edges, so
+    /// graphs of several Vector instances can be combined into one document without their IDs
+    /// clashing. Labels still show the unprefixed IDs.
+    ///
+    /// The prefix may only contain ASCII letters, digits, and `_`, which every format can use
+    /// as is, so it is the same in all of them.
+    #[arg(long, value_name = "PREFIX", value_parser = parse_id_prefix)]
+    pub node_id_prefix: Option<String>,
+
+    /// Report components that feed back into themselves instead of failing to load the config.
+    ///
+    /// A cyclic config is still rendered, but `vector graph` exits with an error afterwards.
+    #[arg(long)]
+    pub detect_cycles: bool,
+
+    /// Draw the edges that are part of a cycle in red. Implies `--detect-cycles`.
+    #[arg(long)]
+    pub highlight_cycles: bool,
+
+    /// Check that every component's inputs refer to an existing source or transform, print
+    /// each one that doesn't, and exit without rendering.
+    #[arg(long)]
+    pub check_inputs_exist: bool,
+
+    /// Report the transforms that don't feed any sink, even through other transforms, along
+    /// with the fraction of all transforms they make up, and exit without rendering.
+    ///
+    /// Inputs naming a component defined in another config file are listed too, so configs
+    /// split into files such as `sources.toml` and `sinks.toml` can be checked to connect the
+    /// way they are meant to. Inputs that don't resolve at all fail to load as usual.
+    #[arg(long)]
+    pub lint: bool,
+
+    /// Exit with an error from `--lint` if more than this percentage of transforms don't feed
+    /// any sink.
+    #[arg(
+        long,
+        value_name = "PCT",
+        requires = "lint",
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    pub fail_if_dead_end_ratio: Option<u8>,
+
+    /// Instead of rendering, print a report on the health of the config: metrics such as the
+    /// number of components of each kind, and findings such as cycles, inputs that don't
+    /// exist, and transforms that don't feed any sink.
+    ///
+    /// The config is reported on even if those findings keep it from building.
+    #[arg(long, value_name = "FORMAT")]
+    pub report: Option<ReportFormat>,
+
+    /// Print the IDs of every component of this type, such as `remap` or `http`, grouped by
+    /// kind, and exit without rendering.
+    #[arg(long, value_name = "TYPE")]
+    pub list_by_type: Option<String>,
+
+    /// Print how many components of each type the config has, grouped by kind, as a table,
+    /// and exit without rendering.
+    #[arg(long, conflicts_with = "list_by_type")]
+    pub count_by_type: bool,
+
+    /// Print the sources whose events reach this component, usually a sink, through any number
+    /// of transforms, one ID per line, and exit without rendering.
+    #[arg(long, value_name = "ID")]
+    pub sources_feeding: Option<String>,
+
+    /// Print what this sink does with its events and exit without rendering: its type, where
+    /// it sends events, whether encryption and authentication are configured, and the sources
+    /// whose events reach it.
+    ///
+    /// Only the names of credential settings are printed, never their values.
+    #[arg(long, value_name = "ID")]
+    pub describe_sink: Option<String>,
+
+    /// Check that every input naming a port, such as `route.errors`, reads a port its component
+    /// has, and exit with an error before rendering if one doesn't.
+    ///
+    /// Inputs reading a port of a component with no named outputs at all are reported apart
+    /// from those naming a port the component doesn't have.
+    #[arg(long)]
+    pub strict_ports: bool,
+
+    /// Check that every source feeds some transform or sink, and exit with an error before
+    /// rendering, printing each one that doesn't, if one feeds nothing.
+    #[arg(long)]
+    pub fail_on_orphan_sources: bool,
+
+    /// Color each edge by the type of the component it comes from, such as `route` or
+    /// `filter`, so a stream can be traced by color. Each type always gets the same color.
+    #[arg(long)]
+    pub edge_color_by_source_type: bool,
+
+    /// Read colors for component kinds and types from this TOML or JSON file, and draw each
+    /// component in the color for its type, or failing that its kind.
+    ///
+    /// The file has a `kinds` table keyed by `source`, `transform`, or `sink` and a `types`
+    /// table keyed by component type, such as `remap = "#ff7f0e"`. Types it leaves out keep
+    /// their default color with `--edge-color-by-source-type`.
+    #[arg(long)]
+    pub palette: Option<PathBuf>,
+
+    /// Compare the topology against the one defined by these config files, treating them as the
+    /// old version.
+    #[arg(long, value_delimiter(','), conflicts_with = "only_external_sinks")]
+    pub compare_to: Vec<PathBuf>,
+
+    /// How to report the differences found with `--compare-to`.
+    #[arg(long, default_value = "graph", requires = "compare_to")]
+    pub diff_format: DiffFormat,
+
+    /// Render images with the Kroki server at this URL instead of a local GraphViz install,
+    /// e.g. `https://kroki.io`.
+    ///
+    /// Requires an image `--format`.
+    #[arg(long)]
+    pub kroki_url: Option<Url>,
+
+    /// Include Vector's internal components, and everything fed only by them.
+    ///
+    /// These are the `internal_logs` and `internal_metrics` sources, along with the components
+    /// Vector adds itself when reporting to Datadog Observability Pipelines is enabled. They are
+    /// hidden by default to keep diagrams focused on the data pipeline.
+    #[arg(long)]
+    pub show_internal: bool,
+
+    /// Label components with the name given in a `# label: <name>` comment directly above
+    /// their declaration, instead of their ID.
+    ///
+    /// Components defined in their own file in a config directory can also be labeled by a
+    /// comment at the top of the file.
+    #[arg(long)]
+    pub use_label_comments: bool,
+
+    /// Clean up labels read from comments so messy comments can't break a diagram: line breaks,
+    /// tabs, and escapes such as `\n` become spaces, double quotes become single quotes, and
+    /// braces become parentheses. This is the default.
+    #[arg(
+        long,
+        requires = "use_label_comments",
+        overrides_with = "no_strip_comments_from_labels"
+    )]
+    pub strip_comments_from_labels: bool,
+
+    /// Use labels read from comments exactly as written, apart from the escaping every format
+    /// needs.
+    #[arg(long, requires = "use_label_comments")]
+    pub no_strip_comments_from_labels: bool,
+
+    /// Show the component with ID `OLD` labeled `NEW`, such as `internal_db_sink=Database`, to
+    /// rename or anonymize it. Edges still connect it by its ID. Can be given more than once, and
+    /// takes precedence over `--use-label-comments`.
+    #[arg(long, value_name = "OLD=NEW", value_parser = parse_relabel)]
+    pub relabel: Vec<(String, String)>,
+
+    /// A JSON object of Mermaid configuration, such as `{"theme": "dark"}`, written as an
+    /// `%%{init: ...}%%` directive at the top of Mermaid output.
+    #[arg(long)]
+    pub mermaid_init: Option<String>,
+
+    /// The background color of the diagram, such as `transparent` or `#1e1e1e`, for embedding
+    /// it in pages with a dark theme.
+    ///
+    /// Sets `bgcolor` in DOT output. Mermaid output gets it as the `background` theme variable
+    /// in its init directive, unless `--mermaid-init` already sets one.
+    #[arg(long, value_name = "COLOR")]
+    pub bgcolor: Option<String>,
+
+    /// Warn when Mermaid output has more than this many edges, since browsers, including
+    /// GitHub's Mermaid previews, may fail to render it.
+    #[arg(long, value_name = "N", default_value = "500")]
+    pub mermaid_max_edges: usize,
+
+    /// How to lay out `json` output.
+    #[arg(long, default_value = "graph")]
+    pub json_shape: JsonShape,
+
+    /// Annotate each node in `json` output with its layer: 0 for components without inputs,
+    /// and otherwise one more than the deepest of their inputs, so clients can lay out the
+    /// graph without walking it.
+    ///
+    /// If the graph has a cycle, layers are left out, and `graph` output sets `"cyclic": true`
+    /// instead. Adjacency maps have no node objects and aren't annotated.
+    #[arg(long)]
+    pub with_layers: bool,
+
+    /// Draw a box around each group of components sharing an ID prefix or type.
+    ///
+    /// Only DOT and Mermaid output draw clusters.
+    #[arg(long, group = "clustering")]
+    pub cluster_by: Option<ClusterBy>,
+
+    /// Draw each source in a box of its own, a swimlane, along with the components whose events
+    /// come only from it. Components reached from more than one source share a lane.
+    ///
+    /// Only DOT and Mermaid output draw swimlanes.
+    #[arg(long, group = "clustering")]
+    pub swimlanes: bool,
+
+    /// Leave out clusters none of whose components are left after filtering, instead of drawing
+    /// them empty.
+    #[arg(long, requires = "cluster_by")]
+    pub no_empty_clusters: bool,
+
+    /// Warn about each cluster drawn with more than this many components, which is a sign that
+    /// `--cluster-by` groups too coarsely to keep the diagram readable.
+    #[arg(long, value_name = "N", default_value = "50")]
+    pub node_limit_per_cluster: usize,
+
+    /// Draw edges between components in different clusters or swimlanes thick and orange, to
+    /// show where they depend on each other. Edges into or out of components outside any
+    /// cluster count as crossing.
+    #[arg(long, requires = "clustering")]
+    pub highlight_cross_cluster: bool,
+
+    /// Label DOT nodes with HTML-like tables: the name on a title bar colored by the
+    /// component's kind, with its type in a row below. Also applies to `svg` and `png` images.
+    #[arg(long)]
+    pub html_labels: bool,
+
+    /// Give each DOT edge a tooltip such as `parse (remap) --errors--> out (console)`, naming
+    /// the components it connects, their types, and the port it reads. SVG viewers show it when
+    /// hovering over the edge or its label.
+    #[arg(long)]
+    pub edge_tooltip: bool,
+
+    /// Write DOT output as a `strict digraph`, so GraphViz merges parallel edges, such as those
+    /// from several ports of one transform into the same sink, into one. Also applies to `svg`
+    /// and `png` images.
+    ///
+    /// The merging is done by GraphViz rather than by this command, so the DOT source still has
+    /// every edge. The merged edge keeps the attributes of the last edge it replaces, which means
+    /// only one of the port labels is drawn.
+    #[arg(long)]
+    pub strict_dot: bool,
+
+    /// Lay DOT output out left to right, with every source pinned to the leftmost rank and
+    /// every sink to the rightmost one, even where GraphViz would place them further in. Also
+    /// applies to `svg` and `png` images.
+    #[arg(long)]
+    pub align_io: bool,
+
+    /// Tag the edges into each sink with end-to-end acknowledgements enabled with `[ack]`,
+    /// whether the sink enables them itself or inherits the global `acknowledgements` setting.
+    #[arg(long)]
+    pub show_acks: bool,
+
+    /// Draw the edges along every path into a sink with end-to-end acknowledgements enabled
+    /// with an arrowhead at both ends, since the acknowledgements flow back upstream along them.
+    #[arg(long)]
+    pub show_ack_paths: bool,
+
+    /// Draw sinks that buffer events on disk with a thick outline, since unlike those buffering
+    /// in memory they keep buffered events across restarts. A sink with chained buffers counts
+    /// if any of its stages is on disk.
+    #[arg(long)]
+    pub show_buffer_type: bool,
+
+    /// Draw sinks that don't run a healthcheck at startup with a dashed outline, whether theirs
+    /// is disabled by their own `healthcheck.enabled` or the global `healthchecks.enabled`.
+    ///
+    /// Only sinks have healthchecks, so sources are never marked.
+    #[arg(long)]
+    pub show_healthchecks: bool,
+
+    /// Prefix each label with an icon for its kind, so the diagram reads without knowing the
+    /// shape conventions. Mermaid uses emoji, and DOT spells out the kind.
+    #[arg(long)]
+    pub icons: bool,
+
+    /// Stop GraphViz and fail if laying out an image takes longer than this many seconds.
+    ///
+    /// By default `dot` may run for as long as it needs, which can be minutes on a large
+    /// topology.
+    #[arg(long, value_name = "SECS")]
+    pub render_timeout: Option<u64>,
+
+    /// The GraphViz layout engine used to render `svg` and `png` images. `sfdp` lays out very
+    /// large graphs much faster than `dot`.
+    #[arg(long, default_value = "dot", conflicts_with = "kroki_url")]
+    pub render_engine: RenderEngine,
+
+    /// The text format sent to the Kroki server to be rendered.
+    #[arg(long, default_value = "dot", requires = "kroki_url")]
+    pub kroki_source: KrokiSource,
+
+    /// Only render these components, along with every component upstream and downstream of
+    /// them.
+    #[arg(long, value_delimiter(','))]
+    pub filter: Vec<String>,
+
+    /// Read more `--filter` components from a file, one ID per line. Blank lines and lines
+    /// starting with `#` are ignored.
+    #[arg(long)]
+    pub seed_from_file: Option<PathBuf>,
+
+    /// Only render the components whose definition changed since this git revision, such as
+    /// `main` or `HEAD~3`, along with the components directly connected to them.
+    ///
+    /// Each config file is compared against its contents at the revision, read with `git`.
+    /// Components that didn't exist then count as changed.
+    #[arg(long, value_name = "REV")]
+    pub changed_since: Option<String>,
+
+    /// Print every path from a source to a component with no outputs, one per line as
+    /// `source -> transform -> sink`, instead of rendering the graph.
+    #[arg(long, conflicts_with = "compare_to")]
+    pub transform_chains: bool,
+
+    /// The most paths `--transform-chains` prints. A warning is logged when paths are left out.
+    #[arg(long, default_value = "1000", requires = "transform_chains")]
+    pub max_chains: usize,
+
+    /// Parse up to this many config files at once. Each file or config directory is parsed on
+    /// its own and then merged in order, the same as loading them one by one.
+    #[arg(long, default_value = "1")]
+    pub jobs: NonZeroUsize,
+
+    /// Write a single HTML page to this file with the full diagram, a transforms-only view,
+    /// and what each source feeds, instead of one format.
+    ///
+    /// The diagrams are rendered as SVG with GraphViz, or with `--kroki-url` if set.
+    #[arg(long, conflicts_with_all = ["output", "output_dir"])]
+    pub bundle: Option<PathBuf>,
+
+    /// Keep running and redraw the graph whenever one of the config files changes.
+    ///
+    /// Files in a config directory are watched too, including ones added after starting.
+    /// Changes are found by polling modification times rather than through file system
+    /// notifications, so they are noticed on network file systems and in containers too.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// How many seconds `--watch` waits between checks of the config files' modification
+    /// times. Raise it to put less load on slow file systems.
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value = "1",
+        requires = "watch",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub watch_interval: u64,
+
+    /// With `--watch`, print what changed between redraws instead of the whole graph, one line
+    /// per change such as `+ added edge a -> b` or `- removed node c`.
+    ///
+    /// The graph is drawn in full once at startup. A component whose label or kind changed is
+    /// listed as removed and added.
+    #[arg(
+        long,
+        requires = "watch",
+        conflicts_with_all = ["compare_to", "output_dir", "bundle"]
+    )]
+    pub watch_diff: bool,
+}
+
+/// Sink types that keep data on the host. Every other sink type is treated as external so
+/// an egress audit never hides a sink it doesn't know about.
+const LOCAL_SINK_TYPES: &[&str] = &["blackhole", "console", "file"];
+
+/// Source types that observe Vector itself rather than ingesting data.
+const INTERNAL_SOURCE_TYPES: &[&str] = &["internal_logs", "internal_metrics"];
+
+impl Opts {
+    /// The format of the config to read from stdin, if one should be.
+    const fn stdin_format(&self) -> Option<config::Format> {
+        if self.config_stdin_toml {
+            Some(config::Format::Toml)
+        } else if self.config_stdin_json {
+            Some(config::Format::Json)
+        } else if self.config_stdin_yaml {
+            Some(config::Format::Yaml)
+        } else {
+            None
+        }
+    }
+
+    fn paths_with_formats(&self) -> Vec<config::ConfigPath> {
+        config::merge_path_lists(vec![
+            (&self.paths, None),
+            (&self.paths_toml, Some(config::Format::Toml)),
+            (&self.paths_json, Some(config::Format::Json)),
+            (&self.paths_yaml, Some(config::Format::Yaml)),
+        ])
+        .map(|(path, hint)| match hint {
+            Some(_) => config::ConfigPath::File(path, hint),
+            None => match split_format_suffix(&path) {
+                Some((path, format)) => config::ConfigPath::File(path, Some(format)),
+                None => config::ConfigPath::File(path, None),
+            },
+        })
+        .chain(
+            self.config_dirs
+                .iter()
+                .map(|dir| config::ConfigPath::Dir(dir.to_path_buf())),
+        )
+        .collect()
+    }
+}
+
+pub(crate) async fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    if opts.formats.len() > 1 && opts.output_dir.is_none() && opts.bundle.is_none() {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("Rendering more than one --format requires --output-dir.");
+        }
+        return exitcode::USAGE;
+    }
+
+    let images_only = opts.formats.iter().all(|format| format.is_image());
+    if opts.clipboard && opts.formats.iter().any(|format| format.is_image()) {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("--clipboard only copies text formats such as dot or mermaid.");
+        }
+        return exitcode::USAGE;
+    }
+
+    if opts.kroki_url.is_some() && !images_only && opts.bundle.is_none() {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("--kroki-url requires an image --format such as svg or png, or --bundle.");
+        }
+        return exitcode::USAGE;
+    }
+
+    if let Some(init) = &opts.mermaid_init {
+        if let Err(error) = validate_mermaid_init(init) {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Invalid --mermaid-init: {}", error);
+            }
+            return exitcode::USAGE;
+        }
+    }
+
+    if let Some(dir) = &opts.output_dir {
+        if let Err(error) = prepare_output_dir(dir) {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Cannot write to --output-dir {:?}: {}", dir, error);
+            }
+            return exitcode::CANTCREAT;
+        }
+    }
+
+    let mut seeds = opts
+        .filter
+        .iter()
+        .map(|id| ComponentKey::from(id.as_str()))
+        .collect::<Vec<_>>();
+    if let Some(path) = &opts.seed_from_file {
+        match read_seeds(path) {
+            Ok(more) => seeds.extend(more),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to read --seed-from-file {:?}: {}", path, error);
+                }
+                return exitcode::NOINPUT;
+            }
+        }
+    }
+
+    // Stdin can only be read once, which is why it can't be combined with `--watch`.
+    let stdin = match opts.stdin_format() {
+        Some(format) => match io::read_to_string(io::stdin()) {
+            Ok(contents) => Some((contents, format)),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to read the config from stdin: {}", error);
+                }
+                return exitcode::NOINPUT;
+            }
+        },
+        None => None,
+    };
+
+    let paths = opts.paths_with_formats();
+    // A config from stdin stands in for the default config path.
+    let paths = if paths.is_empty() && stdin.is_some() {
+        paths
+    } else {
+        match config::process_paths(&paths) {
+            Some(paths) => paths,
+            None => return exitcode::CONFIG,
+        }
+    };
+
+    if opts.watch {
+        watch(opts, &paths, &seeds).await
+    } else {
+        draw(opts, &paths, stdin.as_ref(), &seeds, &mut None).await
+    }
+}
+
+/// Redraws the graph every time one of the config files changes. Failures are reported and
+/// the config is watched for the next change, so this only returns when interrupted.
+async fn watch(
+    opts: &Opts,
+    paths: &[config::ConfigPath],
+    seeds: &[ComponentKey],
+) -> exitcode::ExitCode {
+    let interval = Duration::from_secs(opts.watch_interval);
+    let mut last = None;
+    let mut fingerprint = config_fingerprint(paths);
+    loop {
+        if draw(opts, paths, None, seeds, &mut last).await != exitcode::OK {
+            warn!("Waiting for the configuration to change.");
+        }
+
+        loop {
+            tokio::time::sleep(interval).await;
+            let current = config_fingerprint(paths);
+            if current != fingerprint {
+                info!("Configuration file changed.");
+                fingerprint = current;
+                break;
+            }
+        }
+    }
+}
+
+/// Loads the config, including any read from stdin, and writes the graph. When watching, `last`
+/// holds the topology drawn before, which `--watch-diff` reports the changes from.
+async fn draw(
+    opts: &Opts,
+    paths: &[config::ConfigPath],
+    stdin: Option<&(String, config::Format)>,
+    seeds: &[ComponentKey],
+    last: &mut Option<Topology>,
+) -> exitcode::ExitCode {
+    let (builder, mut warnings, mut origins) = match load_builder(opts, paths, stdin) {
+        Ok(loaded) => loaded,
+        Err(errs) => {
+            #[allow(clippy::print_stderr)]
+            for err in errs {
+                eprintln!("{}", err);
+            }
+            return exitcode::CONFIG;
+        }
+    };
+
+    if opts.strip_config_dir_prefix {
+        strip_common_dir(&mut origins);
+    }
+
+    if opts.print_merged_components {
+        #[allow(clippy::print_stderr)]
+        for line in merged_components(&builder, &origins) {
+            eprintln!("{}", line);
+        }
+        if !opts.show_origin {
+            origins.clear();
+        }
+    }
+
+    let empty =
+        builder.sources.is_empty() && builder.transforms.is_empty() && builder.sinks.is_empty();
+    if empty && opts.fail_on_empty {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("The configuration has no components.");
+        }
+        return exitcode::DATAERR;
+    }
+
+    let changed = match &opts.changed_since {
+        Some(rev) => match changed::changed_since(paths, rev) {
+            Ok(changed) => Some(changed),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!(
+                        "Failed to compare against --changed-since {:?}: {}",
+                        rev, error
+                    );
+                }
+                return exitcode::NOINPUT;
+            }
+        },
+        None => None,
+    };
+
+    if opts.check_inputs_exist {
+        return check_inputs_exist(&builder);
+    }
+    if opts.strict_ports {
+        if let Err(code) = check_ports(&builder) {
+            return code;
+        }
+    }
+    if opts.fail_on_orphan_sources {
+        if let Err(code) = check_orphan_sources(&builder) {
+            return code;
+        }
+    }
+
+    if let Some(ReportFormat::Json) = opts.report {
+        let report = report::Report::new(&builder);
+        return write_output(opts, "json", |w| report.write_json(w))
+            .map_or_else(report_write_error, |()| exitcode::OK);
+    }
+
+    if let Some(id) = &opts.describe_sink {
+        return describe_sink(opts, &builder, id);
+    }
+
+    let mut index = ComponentIndex::new(&builder);
+
+    if let Some(component_type) = &opts.list_by_type {
+        return write_output(opts, "txt", |w| {
+            write_components_of_type(&index, component_type, w)
+        })
+        .map_or_else(report_write_error, |()| exitcode::OK);
+    }
+
+    if opts.count_by_type {
+        return write_output(opts, "txt", |w| write_type_counts(&index, w))
+            .map_or_else(report_write_error, |()| exitcode::OK);
+    }
+
+    // Serializing every component is only worth it for the format that shows the result.
+    let settings = if opts.formats.contains(&OutputFormat::MermaidClass) {
+        component_settings(&builder)
+    } else {
+        HashMap::new()
+    };
+    let acknowledged = if opts.show_acks || opts.show_ack_paths {
+        acknowledged_sinks(&builder)
+    } else {
+        HashSet::new()
+    };
+    let unchecked = if opts.show_healthchecks {
+        sinks_without_healthchecks(&builder)
+    } else {
+        HashSet::new()
+    };
+    // The builder is consumed by building the config, which `--lint` needs to get to first.
+    let cross_file = if opts.lint {
+        cross_file_inputs(&builder, &origins)
+    } else {
+        Vec::new()
+    };
+    let disk_buffered = if opts.show_buffer_type {
+        disk_buffered_sinks(&builder)
+    } else {
+        HashSet::new()
+    };
+
+    let (mut topology, cyclic) =
+        match build_topology(opts, builder, empty, seeds, &mut index, &mut warnings) {
+            Ok(built) => built,
+            Err(code) => return code,
+        };
+    // A cyclic config is still drawn, but drawing it doesn't count as success.
+    let done = if cyclic {
+        exitcode::CONFIG
+    } else {
+        exitcode::OK
+    };
+
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+
+    if opts.lint {
+        return lint(opts, &topology, &index, &cross_file);
+    }
+
+    if let Some(id) = &opts.sources_feeding {
+        return write_sources_feeding(opts, &topology, &index, id);
+    }
+
+    if let Err(code) = narrow(opts, &mut topology, &index, seeds, changed.as_ref()) {
+        return code;
+    }
+
+    if opts.no_quote_ids {
+        let prefix = opts.node_id_prefix.as_deref().unwrap_or_default();
+        let unquotable = dot::unquotable_ids(&topology, prefix);
+        if !unquotable.is_empty() {
+            #[allow(clippy::print_stderr)]
+            for id in unquotable {
+                eprintln!("Component ID {:?} must be quoted in DOT.", id.id());
+            }
+            return exitcode::DATAERR;
+        }
+    }
+
+    if opts.transform_chains {
+        return write_chains(opts, &topology).map_or_else(report_write_error, |()| done);
+    }
+
+    let mut styles = match styles(
+        opts,
+        &topology,
+        &index,
+        &unchecked,
+        &disk_buffered,
+        &acknowledged,
+    ) {
+        Ok(styles) => styles,
+        Err(code) => return code,
+    };
+
+    if !opts.compare_to.is_empty() {
+        let old = match load_compared(opts, seeds, changed.as_ref()) {
+            Ok(old) => old,
+            Err(code) => return code,
+        };
+
+        let diff = TopologyDiff::new(&old, &topology);
+        match opts.diff_format {
+            DiffFormat::Text => {
+                return write_output(opts, "diff", |w| diff.write_text(w))
+                    .map_or_else(report_write_error, |()| exitcode::OK);
+            }
+            DiffFormat::Graph => (topology, styles) = diff.overlay(&topology),
+        }
+    }
+
+    relabel(opts, paths, &mut topology);
+
+    if opts.watch_diff {
+        // The first draw has nothing to compare against, so it renders the whole graph.
+        if let Some(old) = last.replace(topology.clone()) {
+            let diff = TopologyDiff::new(&old, &topology);
+            if diff.is_empty() {
+                return exitcode::OK;
+            }
+            return write_output(opts, "diff", |w| diff.write_text(w))
+                .map_or_else(report_write_error, |()| exitcode::OK);
+        }
+    }
+
+    let clusters = drawn_clusters(opts, &topology, &index, &mut styles);
+
+    let options = graph_options(
+        opts,
+        &index,
+        origins,
+        styles,
+        settings,
+        clusters,
+        acknowledged,
+    );
+    match write_graph(opts, &topology, &index, &options).await {
+        Ok(()) => done,
+        Err(code) => code,
+    }
+}
+
+/// Reports every input that names a component that doesn't exist, for `--check-inputs-exist`.
+fn check_inputs_exist(builder: &config::ConfigBuilder) -> exitcode::ExitCode {
+    let dangling = dangling_inputs(builder);
+    #[allow(clippy::print_stderr)]
+    for (component, input) in &dangling {
+        eprintln!(
+            "Component {:?} has input {:?}, which does not exist.",
+            component.id(),
+            input
+        );
+    }
+    if dangling.is_empty() {
+        exitcode::OK
+    } else {
+        exitcode::DATAERR
+    }
+}
+
+/// Reports every input that reads from a named output its upstream doesn't have, for
+/// `--strict-ports`.
+fn check_ports(builder: &config::ConfigBuilder) -> Result<(), exitcode::ExitCode> {
+    let misused = misused_ports(builder);
+    #[allow(clippy::print_stderr)]
+    for (component, input, misuse) in &misused {
+        // Only inputs of the form `upstream.port` are reported.
+        let (upstream, port) = input.rsplit_once('.').unwrap_or((input, ""));
+        match misuse {
+            PortMisuse::NoPorts => eprintln!(
+                "Component {:?} has input {:?}, but {:?} has no named outputs.",
+                component.id(),
+                input,
+                upstream
+            ),
+            PortMisuse::UnknownPort => eprintln!(
+                "Component {:?} has input {:?}, but {:?} has no output named {:?}.",
+                component.id(),
+                input,
+                upstream,
+                port
+            ),
+        }
+    }
+    if misused.is_empty() {
+        Ok(())
+    } else {
+        Err(exitcode::DATAERR)
+    }
+}
+
+/// Reports every source nothing reads from, for `--fail-on-orphan-sources`.
+fn check_orphan_sources(builder: &config::ConfigBuilder) -> Result<(), exitcode::ExitCode> {
+    let orphans = orphan_sources(builder);
+    #[allow(clippy::print_stderr)]
+    for id in &orphans {
+        eprintln!("Source {:?} is not an input of any component.", id.id());
+    }
+    if orphans.is_empty() {
+        Ok(())
+    } else {
+        Err(exitcode::DATAERR)
+    }
+}
+
+/// Writes what the sink `id` does with its events, for `--describe-sink`.
+fn describe_sink(opts: &Opts, builder: &config::ConfigBuilder, id: &str) -> exitcode::ExitCode {
+    let description = match describe::SinkDescription::new(builder, &id.into()) {
+        Some(description) => description,
+        None => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("--describe-sink {:?} is not a sink.", id);
+            }
+            return exitcode::DATAERR;
+        }
+    };
+    write_output(opts, "txt", |w| description.write_text(w))
+        .map_or_else(report_write_error, |()| exitcode::OK)
+}
+
+/// Builds the topology to draw, and whether it has cycles. Indexes its edges as soon as they are
+/// known, so `--filter` can pick out its neighborhoods before the topology is copied out of the
+/// config.
+fn build_topology(
+    opts: &Opts,
+    builder: config::ConfigBuilder,
+    empty: bool,
+    seeds: &[ComponentKey],
+    index: &mut ComponentIndex,
+    warnings: &mut Vec<String>,
+) -> Result<(Topology, bool), exitcode::ExitCode> {
+    // A cyclic config can't be built, so it is rendered straight from the builder instead.
+    if opts.detect_cycles || opts.highlight_cycles {
+        let topology = Topology::from_builder(&builder);
+        let cycles = topology.cycles();
+        if !cycles.is_empty() {
+            #[allow(clippy::print_stderr)]
+            for cycle in cycles {
+                let ids = cycle.iter().map(|id| id.id()).collect::<Vec<_>>();
+                eprintln!(
+                    "Cyclic dependency detected between components [ {} ]",
+                    ids.join(", ")
+                );
+            }
+            *index = mem::take(index).connect(topology.endpoints());
+            return Ok((topology, true));
+        }
+    }
+
+    // Vector refuses to build a config without sources and sinks, so an empty one is drawn
+    // without building it.
+    if empty {
+        return Ok((Topology::default(), false));
+    }
+
+    match builder.build_with_warnings() {
+        Ok((config, build_warnings)) => {
+            warnings.extend(build_warnings);
+
+            #[cfg(feature = "enterprise")]
+            let mut config = config;
+            #[cfg(feature = "enterprise")]
+            if opts.show_internal {
+                attach_enterprise_components(&mut config);
+            }
+
+            *index = mem::take(index)
+                .connect(edges_iter(&config).map(|edge| (edge.source, edge.target)));
+            let topology = if seeds.is_empty() {
+                Topology::from_config(&config)
+            } else {
+                // Only copy the neighborhoods out of the config rather than every edge.
+                let neighborhoods = neighborhoods(index, seeds, |_| true);
+                Topology::from_config_where(&config, |id| neighborhoods.contains(id))
+            };
+            Ok((topology, false))
+        }
+        Err(errs) => {
+            #[allow(clippy::print_stderr)]
+            for err in errs {
+                eprintln!("{}", err);
+            }
+            Err(exitcode::CONFIG)
+        }
+    }
+}
+
+/// Reports inputs defined in other files and transforms that feed no sink, for `--lint`.
+fn lint(
+    opts: &Opts,
+    topology: &Topology,
+    index: &ComponentIndex,
+    cross_file: &[(ComponentKey, String, PathBuf)],
+) -> exitcode::ExitCode {
+    let dead_ends = dead_end_transforms(topology, index);
+    let (total, percent) = share_of_transforms(topology, dead_ends.len());
+    #[allow(clippy::print_stderr)]
+    {
+        for (component, input, origin) in cross_file {
+            eprintln!(
+                "Component {:?} has input {:?}, which is defined in another file, {:?}.",
+                component.id(),
+                input,
+                origin
+            );
+        }
+        for id in &dead_ends {
+            eprintln!("Transform {:?} does not feed any sink.", id.id());
+        }
+        eprintln!(
+            "{} of {} transforms ({:.1}%) are dead ends.",
+            dead_ends.len(),
+            total,
+            percent
+        );
+    }
+    match opts.fail_if_dead_end_ratio {
+        Some(threshold) if percent > f64::from(threshold) => exitcode::DATAERR,
+        _ => exitcode::OK,
+    }
+}
+
+/// Writes the sources that the component `id` reads from, for `--sources-feeding`.
+fn write_sources_feeding(
+    opts: &Opts,
+    topology: &Topology,
+    index: &ComponentIndex,
+    id: &str,
+) -> exitcode::ExitCode {
+    let id = ComponentKey::from(id);
+    if !topology.nodes.iter().any(|node| node.id == id) {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("--sources-feeding {:?} is not a component.", id.id());
+        }
+        return exitcode::DATAERR;
+    }
+    let sources = sources_feeding(topology, index, id);
+    write_output(opts, "txt", |w| {
+        sources.iter().try_for_each(|id| writeln!(w, "{}", id))
+    })
+    .map_or_else(report_write_error, |()| exitcode::OK)
+}
+
+/// Narrows the topology down to the components the filters select, each applied to what the
+/// ones before it left.
+fn narrow(
+    opts: &Opts,
+    topology: &mut Topology,
+    index: &ComponentIndex,
+    seeds: &[ComponentKey],
+    changed: Option<&HashSet<ComponentKey>>,
+) -> Result<(), exitcode::ExitCode> {
+    if !opts.show_internal {
+        let internal_sources = index.matching(ComponentKind::Source, |component_type| {
+            INTERNAL_SOURCE_TYPES.contains(&component_type)
+        });
+        let internal = fed_only_by(topology, index, internal_sources);
+        topology.retain(|node| !internal.contains(&node.id));
+    }
+
+    if opts.only_external_sinks {
+        let external_sinks = index.matching(ComponentKind::Sink, |component_type| {
+            !LOCAL_SINK_TYPES.contains(&component_type)
+        });
+        let chains = upstream_chains(topology, index, external_sinks);
+        topology.retain(|node| chains.contains(&node.id));
+    }
+
+    if !opts.reaching_sink_type.is_empty() {
+        let typed_sinks = index.matching(ComponentKind::Sink, |component_type| {
+            opts.reaching_sink_type
+                .iter()
+                .any(|sink_type| sink_type == component_type)
+        });
+        if typed_sinks.is_empty() {
+            warn!(
+                "No sinks are of type {}.",
+                opts.reaching_sink_type.join(" or ")
+            );
+        }
+        let chains = upstream_chains(topology, index, typed_sinks);
+        topology.retain(|node| chains.contains(&node.id));
+    }
+
+    if opts.only_reachable_from_sources {
+        let reachable = reachable_from_sources(topology, index);
+        topology.retain(|node| reachable.contains(&node.id));
+    }
+
+    if !seeds.is_empty() {
+        let ids = topology.ids();
+        let missing = seeds
+            .iter()
+            .filter(|id| !ids.contains(id))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            #[allow(clippy::print_stderr)]
+            for id in missing {
+                eprintln!(
+                    "Component {:?} given to --filter is not in the graph.",
+                    id.id()
+                );
+            }
+            return Err(exitcode::DATAERR);
+        }
+
+        let neighborhoods = neighborhoods(index, seeds, |id| ids.contains(id));
+        topology.retain(|node| neighborhoods.contains(&node.id));
+    }
+
+    if let Some(changed) = changed {
+        if changed.is_empty() {
+            warn!(
+                "No components changed since {:?}.",
+                opts.changed_since.as_deref().unwrap_or_default()
+            );
+        }
+        let neighbors = with_neighbors(topology, index, changed);
+        topology.retain(|node| neighbors.contains(&node.id));
+    }
+
+    if let Some(min_degree) = opts.min_degree {
+        let mut degrees = HashMap::<_, usize>::new();
+        for edge in &topology.edges {
+            *degrees.entry(edge.source.clone()).or_default() += 1;
+            *degrees.entry(edge.target.clone()).or_default() += 1;
+        }
+        topology.retain(|node| degrees.get(&node.id).copied().unwrap_or_default() >= min_degree);
+        let components = topology.connected_components();
+        if components > 1 {
+            warn!(
+                "The components with --min-degree {} fall into {} disconnected groups.",
+                min_degree, components
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes every chain of transforms from a source to a sink, for `--transform-chains`.
+fn write_chains(opts: &Opts, topology: &Topology) -> Result<(), (PathBuf, io::Error)> {
+    let (chains, truncated) = topology.chains(opts.max_chains);
+    if truncated {
+        warn!(
+            "Only the first {} chains are listed; raise --max-chains to see the rest.",
+            opts.max_chains
+        );
+    }
+
+    write_output(opts, "txt", |w| {
+        for chain in chains {
+            let ids = chain.iter().map(|id| id.id()).collect::<Vec<_>>();
+            writeln!(w, "{}", ids.join(" -> "))?;
+        }
+        Ok(())
+    })
+}
+
+/// Styles the nodes and edges the options call out: cycles, colors from `--palette`, sinks
+/// without healthchecks or with disk buffers, and the paths to acknowledged sinks.
+fn styles(
+    opts: &Opts,
+    topology: &Topology,
+    index: &ComponentIndex,
+    unchecked: &HashSet<ComponentKey>,
+    disk_buffered: &HashSet<ComponentKey>,
+    acknowledged: &HashSet<ComponentKey>,
+) -> Result<Styles, exitcode::ExitCode> {
+    let mut styles = Styles::default();
+    if opts.highlight_cycles {
+        for index in topology.cycle_edges() {
+            styles.edges.insert(index, Style::color("red"));
+        }
+    }
+
+    let palette = match &opts.palette {
+        Some(path) => match read_palette(path) {
+            Ok(palette) => palette,
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Failed to read --palette {:?}: {}", path, error);
+                }
+                return Err(exitcode::NOINPUT);
+            }
+        },
+        None => Palette::default(),
+    };
+
+    for node in &topology.nodes {
+        let component_type = index
+            .get(&node.id)
+            .map(|component| component.component_type);
+        if let Some(color) = palette.component_color(node.kind, component_type) {
+            styles.nodes.insert(node.id.clone(), Style::color(color));
+        }
+    }
+
+    for node in &topology.nodes {
+        if unchecked.contains(&node.id) {
+            styles.nodes.entry(node.id.clone()).or_default().dashed = true;
+        }
+        if disk_buffered.contains(&node.id) {
+            styles.nodes.entry(node.id.clone()).or_default().thick = true;
+        }
+    }
+
+    if opts.edge_color_by_source_type {
+        for (position, edge) in topology.edges.iter().enumerate() {
+            if let Some(component) = index.get(&edge.source) {
+                // Highlighted cycles keep their color.
+                styles
+                    .edges
+                    .entry(position)
+                    .or_insert_with(|| Style::color(palette.type_color(component.component_type)));
+            }
+        }
+    }
+
+    if opts.show_ack_paths {
+        let chains = upstream_chains(topology, index, acknowledged.iter().cloned().collect());
+        for (position, edge) in topology.edges.iter().enumerate() {
+            if chains.contains(&edge.target) {
+                styles.edges.entry(position).or_default().both_ends = true;
+            }
+        }
+    }
+
+    Ok(styles)
+}
+
+/// Labels nodes with the comments above their definitions, for `--use-label-comments`, and
+/// then with the labels given to `--relabel`.
+fn relabel(opts: &Opts, paths: &[config::ConfigPath], topology: &mut Topology) {
+    if opts.use_label_comments {
+        let labels = labels::label_comments(paths);
+        for node in &mut topology.nodes {
+            node.label = labels.get(&node.id).map(|label| {
+                if opts.no_strip_comments_from_labels {
+                    label.clone()
+                } else {
+                    labels::sanitize(label)
+                }
+            });
+        }
+    }
+
+    for (id, label) in &opts.relabel {
+        if let Some(node) = topology.nodes.iter_mut().find(|node| node.id.id() == id) {
+            node.label = Some(label.clone());
+        }
+    }
+}
+
+/// The clusters to draw, from `--swimlanes` or `--cluster-by`, warning about any that are too
+/// big and highlighting the edges between them for `--highlight-cross-cluster`.
+fn drawn_clusters(
+    opts: &Opts,
+    topology: &Topology,
+    index: &ComponentIndex,
+    styles: &mut Styles,
+) -> Vec<Cluster> {
+    let mut clusters = if opts.swimlanes {
+        cluster::Swimlanes::new(topology).into_clusters()
+    } else {
+        opts.cluster_by
+            .map(|by| cluster::clusters(index, by))
+            .unwrap_or_default()
+    };
+    if opts.no_empty_clusters {
+        clusters.retain(|cluster| cluster.is_drawn_in(topology));
+    }
+    for cluster in &clusters {
+        let len = cluster.drawn_len(topology);
+        if len > opts.node_limit_per_cluster {
+            warn!(
+                "Cluster {:?} has {} components, more than --node-limit-per-cluster {}; consider grouping them more finely.",
+                cluster.name, len, opts.node_limit_per_cluster
+            );
+        }
+    }
+
+    if opts.highlight_cross_cluster {
+        for position in cluster::cross_cluster_edges(&clusters, topology) {
+            let style = styles.edges.entry(position).or_default();
+            style.thick = true;
+            // Highlighted cycles and edges colored by type keep their color.
+            style.color.get_or_insert_with(|| "orange".to_owned());
+        }
+    }
+
+    clusters
+}
+
+/// The options to render the graph with, from the command line and what was gathered from the
+/// config.
+fn graph_options(
+    opts: &Opts,
+    index: &ComponentIndex,
+    origins: HashMap<ComponentKey, PathBuf>,
+    styles: Styles,
+    settings: HashMap<ComponentKey, Vec<(String, String)>>,
+    clusters: Vec<Cluster>,
+    acknowledged: HashSet<ComponentKey>,
+) -> GraphOptions {
+    let component_types = if opts.html_labels || opts.edge_tooltip {
+        index
+            .iter()
+            .map(|(id, component)| (id.clone(), component.component_type.to_owned()))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    // `--show-ack-paths` on its own styles the paths without tagging their edges.
+    let acknowledged = if opts.show_acks {
+        acknowledged
+    } else {
+        HashSet::new()
+    };
+
+    let mermaid_init = match &opts.bgcolor {
+        Some(color) => Some(mermaid_init_with_background(
+            opts.mermaid_init.as_deref(),
+            color,
+        )),
+        None => opts.mermaid_init.clone(),
+    };
+    GraphOptions::new()
+        .origins(origins)
+        .quote_ids(!opts.no_quote_ids)
+        .styles(styles)
+        .settings(settings)
+        .clusters(clusters)
+        .acknowledged(acknowledged)
+        .component_types(component_types)
+        .html_labels(opts.html_labels)
+        .edge_tooltips(opts.edge_tooltip)
+        .align_io(opts.align_io)
+        .strict_dot(opts.strict_dot)
+        .json_shape(opts.json_shape)
+        .layers(opts.with_layers)
+        .icons(opts.icons)
+        .mermaid_init(mermaid_init)
+        .bgcolor(opts.bgcolor.clone())
+        .id_prefix(opts.node_id_prefix.clone())
+}
+
+/// Writes the graph as a `--bundle`, in both formats for `--render-both`, or in each `--format`.
+async fn write_graph(
+    opts: &Opts,
+    topology: &Topology,
+    index: &ComponentIndex,
+    options: &GraphOptions,
+) -> Result<(), exitcode::ExitCode> {
+    let mermaid = opts.render_both
+        || opts
+            .formats
+            .iter()
+            .any(|format| matches!(format, OutputFormat::Mermaid | OutputFormat::MermaidClass));
+    if mermaid && topology.edges.len() > opts.mermaid_max_edges {
+        warn!(
+            "The Mermaid diagram has {} edges, more than --mermaid-max-edges {}, and may not render in browsers. Consider `--format dot` or narrowing it down with --filter.",
+            topology.edges.len(),
+            opts.mermaid_max_edges
+        );
+    }
+
+    if let Some(path) = &opts.bundle {
+        return write_bundle(opts, path, topology, index, options).await;
+    }
+
+    if opts.render_both {
+        return write_output(opts, "txt", |w| write_both(topology, options, w))
+            .map_err(report_write_error);
+    }
+
+    // Every format is rendered from the same topology, so the config is only loaded once.
+    for &format in &opts.formats {
+        write_format(opts, format, topology, options).await?;
+    }
+    Ok(())
+}
+
+/// Renders the topology in `format` and writes it out, returning the exit code to fail with if
+/// that isn't possible.
+async fn write_format(
+    opts: &Opts,
+    format: OutputFormat,
+    topology: &Topology,
+    options: &GraphOptions,
+) -> Result<(), exitcode::ExitCode> {
+    let written = if format.is_image() {
+        let source_format = source_format(opts);
+        let write_source = |w: &mut dyn Write| write_text(source_format, topology, options, w);
+
+        let image = match render_image(opts, format, topology, options).await {
+            Ok(image) => image,
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("{}", error);
+                }
+                return Err(render_error_code(&error));
+            }
+        };
+
+        // Keep the source next to the image so it can always be regenerated.
+        write_output(opts, format.extension(), |w| w.write_all(&image)).and_then(|_| {
+            match &opts.output_dir {
+                Some(dir) => {
+                    let name = match source_format {
+                        OutputFormat::Dot => "graph.gv".to_owned(),
+                        format => format!("graph.{}", format.extension()),
+                    };
+                    write_file(&dir.join(name), write_source)
+                }
+                None => Ok(()),
+            }
+        })
+    } else {
+        write_output(opts, format.extension(), |w| {
+            write_text(format, topology, options, w)
+        })
+    };
+
+    written.map_err(report_write_error)
+}
+
+/// The text format images are rendered from.
+const fn source_format(opts: &Opts) -> OutputFormat {
+    match &opts.kroki_url {
+        Some(_) => opts.kroki_source.format(),
+        None => OutputFormat::Dot,
+    }
+}
+
+/// Renders the topology as an image, through Kroki if `--kroki-url` is set and GraphViz
+/// otherwise.
+async fn render_image(
+    opts: &Opts,
+    format: OutputFormat,
+    topology: &Topology,
+    options: &GraphOptions,
+) -> Result<Vec<u8>, RenderError> {
+    let source_format = source_format(opts);
+    let write_source = |w: &mut dyn Write| write_text(source_format, topology, options, w);
+
+    match &opts.kroki_url {
+        Some(url) => {
+            let mut source = Vec::new();
+            write_source(&mut source).expect("write to Vec never fails");
+            kroki::render(
+                url,
+                opts.kroki_source.diagram_type(),
+                format.extension(),
+                source,
+            )
+            .await
+            .map_err(RenderError::Failed)
+        }
+        None => {
+            let timeout = opts.render_timeout.map(Duration::from_secs);
+            graphviz::render(
+                opts.render_engine.binary(),
+                format.extension(),
+                timeout,
+                write_source,
+            )
+        }
+    }
+}
+
+/// A timeout is worth retrying, perhaps with a smaller graph, while other failures need GraphViz
+/// or Kroki fixed first.
+const fn render_error_code(error: &RenderError) -> exitcode::ExitCode {
+    match error {
+        RenderError::TimedOut(_) => exitcode::TEMPFAIL,
+        RenderError::Failed(_) => exitcode::UNAVAILABLE,
+    }
+}
+
+/// Renders every view of the topology and writes them to `--bundle` as one HTML page.
+async fn write_bundle(
+    opts: &Opts,
+    path: &Path,
+    topology: &Topology,
+    index: &ComponentIndex,
+    options: &GraphOptions,
+) -> Result<(), exitcode::ExitCode> {
+    let mut transforms = topology.clone();
+    transforms.retain(|node| node.kind == ComponentKind::Transform);
+    // Edge styles are keyed by index, which no longer lines up once edges are dropped.
+    let mut transforms_options = options.clone();
+    transforms_options.styles.edges.clear();
+
+    let mut sections = Vec::new();
+    for (title, topology, options) in [
+        ("Full topology", topology, options),
+        ("Transforms", &transforms, &transforms_options),
+    ] {
+        match render_image(opts, OutputFormat::Svg, topology, options).await {
+            Ok(svg) => sections.push(bundle::Section {
+                title: title.to_owned(),
+                body: bundle::SectionBody::Svg(svg),
+            }),
+            Err(error) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("{}", error);
+                }
+                return Err(render_error_code(&error));
+            }
+        }
+    }
+    sections.push(bundle::Section {
+        title: "Sources".to_owned(),
+        body: bundle::SectionBody::SourceStats(bundle::source_stats(topology, index)),
+    });
+
+    write_file(path, |w| {
+        bundle::write_bundle("Vector topology", &sections, w)
+    })
+    .map_err(report_write_error)
+}
+
+/// Renders the topology in one of the text formats.
+fn write_text(
+    format: OutputFormat,
+    topology: &Topology,
+    options: &GraphOptions,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Dot => dot::write_dot(topology, options, w),
+        OutputFormat::Mermaid => mermaid::write_mermaid(topology, options, w),
+        OutputFormat::MermaidClass => mermaid::write_mermaid_class(topology, options, w),
+        OutputFormat::Json => json::write_json(topology, options, w),
+        OutputFormat::Svg | OutputFormat::Png => unreachable!("images are rendered from DOT"),
+    }
+}
+
+/// Renders the topology in DOT and then in Mermaid, each under a marker naming it.
+fn write_both(topology: &Topology, options: &GraphOptions, w: &mut dyn Write) -> io::Result<()> {
+    writeln!(w, "=== DOT ===")?;
+    dot::write_dot(topology, options, w)?;
+    writeln!(w)?;
+    writeln!(w, "=== MERMAID ===")?;
+    mermaid::write_mermaid(topology, options, w)
+}
+
+/// Checks that a Mermaid init directive is a JSON object, as Mermaid expects.
+fn validate_mermaid_init(init: &str) -> Result<(), String> {
+    match serde_json::from_str::<serde_json::Value>(init) {
+        Ok(serde_json::Value::Object(_)) => Ok(()),
+        Ok(_) => Err("expected a JSON object".to_owned()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Adds a `background` theme variable to a validated Mermaid init directive, keeping any
+/// background it sets itself.
+fn mermaid_init_with_background(init: Option<&str>, color: &str) -> String {
+    let mut init = init
+        .and_then(|init| serde_json::from_str::<serde_json::Map<_, _>>(init).ok())
+        .unwrap_or_default();
+    let variables = init
+        .entry("themeVariables")
+        .or_insert_with(|| serde_json::json!({}));
+    if let Some(variables) = variables.as_object_mut() {
+        variables
+            .entry("background")
+            .or_insert_with(|| color.into());
+    }
+    serde_json::Value::Object(init).to_string()
+}
+
+/// Creates `dir` if needed and checks a file can be written to it, so a bad `--output-dir` fails
+/// before any time is spent loading and rendering.
+fn prepare_output_dir(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let probe = dir.join(format!(".vector-graph-{}", std::process::id()));
+    File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// Streams the rendered graph to `--output`, `graph.<extension>` in `--output-dir`, or stdout.
+fn write_output(
+    opts: &Opts,
+    extension: &str,
+    render: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), (PathBuf, io::Error)> {
+    let path = match (&opts.output, &opts.output_dir) {
+        (Some(path), _) => path.clone(),
+        (None, Some(dir)) => dir.join(format!("graph.{}", extension)),
+        (None, None) if opts.clipboard => {
+            let mut text = Vec::new();
+            render(&mut text).map_err(|error| (PathBuf::from("clipboard"), error))?;
+            if let Err(error) = clipboard::copy(&text) {
+                warn!("{}; writing to stdout instead.", error);
+                let mut stdout = io::stdout().lock();
+                return stdout
+                    .write_all(&text)
+                    .and_then(|_| stdout.flush())
+                    .map_err(|error| (PathBuf::from("stdout"), error));
+            }
+            return Ok(());
+        }
+        (None, None) => {
+            let mut stdout = BufWriter::new(io::stdout().lock());
+            return render(&mut stdout)
+                .and_then(|_| stdout.flush())
+                .map_err(|error| (PathBuf::from("stdout"), error));
+        }
+    };
+
+    write_file(&path, render)
+}
+
+/// Reports a failure to write the output and returns the exit code to fail with.
+fn report_write_error((path, error): (PathBuf, io::Error)) -> exitcode::ExitCode {
+    #[allow(clippy::print_stderr)]
+    {
+        eprintln!("Failed to write {:?}: {}", path, error);
+    }
+    exitcode::CANTCREAT
+}
+
+fn write_file(
+    path: &Path,
+    render: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), (PathBuf, io::Error)> {
+    File::create(path)
+        .and_then(|file| {
+            let mut w = BufWriter::new(file);
+            render(&mut w)?;
+            w.flush()
+        })
+        .map_err(|error| (path.to_owned(), error))
+}
+
+/// The modification time of every config file, so `--watch` can tell when one changes. Files
+/// that can't be read are included without one, so deleting and recreating them is noticed.
+///
+/// The directories the files are loaded from are included too, since adding a file changes its
+/// directory's time.
+fn config_fingerprint(paths: &[config::ConfigPath]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let modified = |path: PathBuf| {
+        let time = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        (path, time)
+    };
+
+    files::config_dirs(paths)
+        .into_iter()
+        .chain(files::config_files(paths).into_iter().map(|file| file.path))
+        .map(modified)
+        .collect()
+}
+
+/// Describes every component in the merged config on a line of its own, with the file it was
+/// defined in if that is known.
+fn merged_components(
+    builder: &config::ConfigBuilder,
+    origins: &HashMap<ComponentKey, PathBuf>,
+) -> Vec<String> {
+    builder
+        .sources
+        .keys()
+        .map(|id| ("source", id))
+        .chain(builder.transforms.keys().map(|id| ("transform", id)))
+        .chain(builder.sinks.keys().map(|id| ("sink", id)))
+        .map(|(kind, id)| match origins.get(id) {
+            Some(origin) => format!("{} {:?} from {:?}", kind, id.id(), origin),
+            None => format!("{} {:?}", kind, id.id()),
+        })
+        .collect()
+}
+
+/// Collects the given sources along with every component whose inputs all come from them, directly
+/// or transitively.
+fn fed_only_by(
+    topology: &Topology,
+    index: &ComponentIndex,
+    sources: Vec<ComponentKey>,
+) -> HashSet<ComponentKey> {
+    let ids = topology.ids();
+    let mut fed = sources.into_iter().collect::<HashSet<_>>();
+    loop {
+        let newly_fed = topology
+            .nodes
+            .iter()
+            .filter(|node| !fed.contains(&node.id))
+            .filter(|node| {
+                let mut inputs = index
+                    .adjacent(&node.id, Direction::Upstream)
+                    .iter()
+                    .filter(|input| ids.contains(input))
+                    .peekable();
+                inputs.peek().is_some() && inputs.all(|input| fed.contains(input))
+            })
+            .map(|node| node.id.clone())
+            .collect::<Vec<_>>();
+        if newly_fed.is_empty() {
+            return fed;
+        }
+        fed.extend(newly_fed);
+    }
+}
+
+/// Adds the components Vector attaches itself when reporting to Datadog Observability
+/// Pipelines, if the config enables it.
+#[cfg(feature = "enterprise")]
+fn attach_enterprise_components(config: &mut config::Config) {
+    use crate::config::enterprise::{self, EnterpriseMetadata};
+
+    if let Ok(metadata) = EnterpriseMetadata::try_from(&*config) {
+        enterprise::attach_enterprise_components(config, &metadata);
+    }
+}
+
+/// Finds the inputs that don't name a source or transform, either directly or with a `.port`
+/// suffix, after expanding globs. Each is paired with the component that lists it.
+fn dangling_inputs(builder: &config::ConfigBuilder) -> Vec<(ComponentKey, String)> {
+    let mut builder = builder.clone();
+    config::expand_globs(&mut builder);
+
+    let upstream = |id: &str| {
+        let key = ComponentKey::from(id);
+        builder.sources.contains_key(&key) || builder.transforms.contains_key(&key)
+    };
+    let exists = |input: &str| {
+        upstream(input)
+            || input
+                .rsplit_once('.')
+                .map_or(false, |(component, _)| upstream(component))
+    };
+
+    let transforms = builder
+        .transforms
+        .iter()
+        .map(|(id, transform)| (id, &transform.inputs[..]));
+    let sinks = builder
+        .sinks
+        .iter()
+        .map(|(id, sink)| (id, &sink.inputs[..]));
+    transforms
+        .chain(sinks)
+        .flat_map(|(id, inputs)| {
+            inputs
+                .iter()
+                .filter(|input| !exists(input))
+                .map(move |input| (id.clone(), input.clone()))
+        })
+        .collect()
+}
+
+/// Finds the sources no transform or sink lists as an input, directly or with a `.port` suffix,
+/// after expanding globs.
+fn orphan_sources(builder: &config::ConfigBuilder) -> Vec<ComponentKey> {
+    let mut builder = builder.clone();
+    config::expand_globs(&mut builder);
+
+    let inputs = builder
+        .transforms
+        .values()
+        .flat_map(|transform| &transform.inputs)
+        .chain(builder.sinks.values().flat_map(|sink| &sink.inputs))
+        .flat_map(|input| {
+            let component = input.rsplit_once('.').map(|(component, _)| component);
+            std::iter::once(input.as_str()).chain(component)
+        })
+        .collect::<HashSet<_>>();
+    builder
+        .sources
+        .keys()
+        .filter(|id| !inputs.contains(id.id()))
+        .cloned()
+        .collect()
+}
+
+/// Finds the inputs that name a component defined in a different file from the component that
+/// lists them, after expanding globs. Each is paired with the component that lists it and the
+/// file its upstream component is defined in.
+fn cross_file_inputs(
+    builder: &config::ConfigBuilder,
+    origins: &HashMap<ComponentKey, PathBuf>,
+) -> Vec<(ComponentKey, String, PathBuf)> {
+    let mut builder = builder.clone();
+    config::expand_globs(&mut builder);
+
+    let origin = |id: &str| origins.get(&ComponentKey::from(id));
+    let upstream_origin = |input: &str| {
+        origin(input).or_else(|| {
+            input
+                .rsplit_once('.')
+                .and_then(|(component, _)| origin(component))
+        })
+    };
+
+    let transforms = builder
+        .transforms
+        .iter()
+        .map(|(id, transform)| (id, &transform.inputs[..]));
+    let sinks = builder
+        .sinks
+        .iter()
+        .map(|(id, sink)| (id, &sink.inputs[..]));
+    transforms
+        .chain(sinks)
+        .flat_map(|(id, inputs)| {
+            let own = origins.get(id);
+            inputs.iter().filter_map(move |input| {
+                upstream_origin(input)
+                    .filter(|upstream| Some(*upstream) != own)
+                    .map(|upstream| (id.clone(), input.clone(), upstream.clone()))
+            })
+        })
+        .collect()
+}
+
+/// Lists the type and top-level scalar settings of every component in the config, in the order
+/// they serialize in. Tables and arrays such as `encoding` or `inputs` are left out, and so are
+/// credentials, which serialize as plain strings.
+fn component_settings(
+    builder: &config::ConfigBuilder,
+) -> HashMap<ComponentKey, Vec<(String, String)>> {
+    let settings = |inner: serde_json::Result<serde_json::Value>| {
+        inner.map(scalar_settings).unwrap_or_default()
+    };
+
+    let sources = builder
+        .sources
+        .iter()
+        .map(|(id, source)| (id, serde_json::to_value(&source.inner)));
+    let transforms = builder
+        .transforms
+        .iter()
+        .map(|(id, transform)| (id, serde_json::to_value(&transform.inner)));
+    let sinks = builder
+        .sinks
+        .iter()
+        .map(|(id, sink)| (id, serde_json::to_value(&sink.inner)));
+    sources
+        .chain(transforms)
+        .chain(sinks)
+        .map(|(id, inner)| (id.clone(), settings(inner)))
+        .collect()
+}
+
+/// The type and top-level scalar settings of one serialized component, leaving out any setting
+/// [`describe::is_credential`] recognizes and hiding the user name and password of any URL.
+fn scalar_settings(inner: serde_json::Value) -> Vec<(String, String)> {
+    let mut settings = Vec::new();
+    if let serde_json::Value::Object(fields) = inner {
+        // `type` is the tag the component was deserialized by, so it always comes first.
+        if let Some(serde_json::Value::String(component_type)) = fields.get("type") {
+            settings.push(("type".to_owned(), component_type.clone()));
+        }
+        for (key, value) in fields {
+            let value = match value {
+                _ if key == "type" || describe::is_credential(&key) => continue,
+                serde_json::Value::String(value) => describe::redact_userinfo(&value).into_owned(),
+                serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+                _ => continue,
+            };
+            settings.push((key, value));
+        }
+    }
+    settings
+}
+
+/// How an input misuses a port of the component it reads from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PortMisuse {
+    /// The upstream component has no named outputs at all.
+    NoPorts,
+
+    /// The upstream component has named outputs, but none by this name.
+    UnknownPort,
+}
+
+/// Finds every input naming a port of a source or transform that doesn't have that port.
+/// Inputs naming components that don't exist are left to `dangling_inputs`.
+fn misused_ports(builder: &config::ConfigBuilder) -> Vec<(ComponentKey, String, PortMisuse)> {
+    let mut builder = builder.clone();
+    config::expand_globs(&mut builder);
+
+    let namespace = builder.schema.log_namespace();
+    let sources = builder.sources.iter().map(|(id, source)| {
+        let outputs = source.inner.outputs(namespace);
+        (
+            id,
+            outputs
+                .into_iter()
+                .filter_map(|output| output.port)
+                .collect(),
+        )
+    });
+    let transforms = builder.transforms.iter().map(|(id, transform)| {
+        let definitions = [(id.into(), config::schema::Definition::any())];
+        let outputs = transform.inner.outputs(&definitions, namespace);
+        (
+            id,
+            outputs
+                .into_iter()
+                .filter_map(|output| output.port)
+                .collect(),
+        )
+    });
+    let ports = sources
+        .chain(transforms)
+        .collect::<HashMap<&ComponentKey, Vec<String>>>();
+
+    let misuse = |input: &str| {
+        if ports.contains_key(&ComponentKey::from(input)) {
+            return None;
+        }
+        let (component, port) = input.rsplit_once('.')?;
+        let named = ports.get(&ComponentKey::from(component))?;
+        if named.is_empty() {
+            Some(PortMisuse::NoPorts)
+        } else if !named.iter().any(|name| name == port) {
+            Some(PortMisuse::UnknownPort)
+        } else {
+            None
+        }
+    };
+
+    let transforms = builder
+        .transforms
+        .iter()
+        .map(|(id, transform)| (id, &transform.inputs[..]));
+    let sinks = builder
+        .sinks
+        .iter()
+        .map(|(id, sink)| (id, &sink.inputs[..]));
+    transforms
+        .chain(sinks)
+        .flat_map(|(id, inputs)| {
+            inputs.iter().filter_map(move |input| {
+                misuse(input).map(|misuse| (id.clone(), input.clone(), misuse))
+            })
+        })
+        .collect()
+}
+
+/// Collects the given sinks along with all of the components that transitively feed them.
+fn upstream_chains(
+    topology: &Topology,
+    index: &ComponentIndex,
+    sinks: Vec<ComponentKey>,
+) -> HashSet<ComponentKey> {
+    let ids = topology.ids();
+    index.reachable(sinks, Direction::Upstream, |id| ids.contains(id))
+}
+
+/// Collects the sources along with all of the components they transitively feed.
+fn reachable_from_sources(topology: &Topology, index: &ComponentIndex) -> HashSet<ComponentKey> {
+    let ids = topology.ids();
+    let sources = topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Source)
+        .map(|node| node.id.clone());
+    index.reachable(sources, Direction::Downstream, |id| ids.contains(id))
+}
+
+/// Collects the sinks that won't be health checked at startup, because their own healthcheck or
+/// healthchecks as a whole are disabled.
+fn sinks_without_healthchecks(builder: &config::ConfigBuilder) -> HashSet<ComponentKey> {
+    builder
+        .sinks
+        .iter()
+        .filter(|(_, sink)| !(builder.healthchecks.enabled && sink.healthcheck().enabled))
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Collects the sinks with end-to-end acknowledgements enabled, by their own setting or the
+/// global default.
+fn acknowledged_sinks(builder: &config::ConfigBuilder) -> HashSet<ComponentKey> {
+    builder
+        .sinks
+        .iter()
+        .filter(|(_, sink)| {
+            sink.inner
+                .acknowledgements()
+                .merge_default(&builder.global.acknowledgements)
+                .enabled()
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Collects the sinks with a buffer stage on disk.
+fn disk_buffered_sinks(builder: &config::ConfigBuilder) -> HashSet<ComponentKey> {
+    builder
+        .sinks
+        .iter()
+        .filter(|(_, sink)| {
+            sink.buffer
+                .stages()
+                .iter()
+                .any(|stage| matches!(stage, BufferType::DiskV2 { .. }))
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// How many transforms the topology has, and the percentage of them that `count` are.
+fn share_of_transforms(topology: &Topology, count: usize) -> (usize, f64) {
+    let total = topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Transform)
+        .count();
+    let percent = if total == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / total as f64
+    };
+    (total, percent)
+}
+
+/// Writes the IDs of the components of `component_type` under a heading per kind, leaving out
+/// kinds with none.
+fn write_components_of_type(
+    index: &ComponentIndex,
+    component_type: &str,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    let kinds = [
+        (ComponentKind::Source, "sources"),
+        (ComponentKind::Transform, "transforms"),
+        (ComponentKind::Sink, "sinks"),
+    ];
+    for (kind, heading) in kinds {
+        let ids = index.matching(kind, |other| other == component_type);
+        if !ids.is_empty() {
+            writeln!(w, "{}:", heading)?;
+            for id in ids {
+                writeln!(w, "  {}", id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes how many components of each type there are under a heading per kind, leaving out
+/// kinds with none. Types are in alphabetical order, and counts are aligned across kinds.
+fn write_type_counts(index: &ComponentIndex, w: &mut dyn Write) -> io::Result<()> {
+    let mut counts = BTreeMap::<_, BTreeMap<_, usize>>::new();
+    for (_, component) in index.iter() {
+        *counts
+            .entry(component.kind)
+            .or_default()
+            .entry(component.component_type)
+            .or_default() += 1;
+    }
+    let width = counts
+        .values()
+        .flat_map(BTreeMap::keys)
+        .map(|component_type| component_type.len())
+        .max()
+        .unwrap_or_default();
+
+    for (kind, types) in counts {
+        let heading = match kind {
+            ComponentKind::Source => "sources",
+            ComponentKind::Transform => "transforms",
+            ComponentKind::Sink => "sinks",
+        };
+        writeln!(w, "{}:", heading)?;
+        for (component_type, count) in types {
+            writeln!(w, "  {:width$}  {}", component_type, count, width = width)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the sources that `id` transitively reads from, in the order they appear in the topology.
+fn sources_feeding(
+    topology: &Topology,
+    index: &ComponentIndex,
+    id: ComponentKey,
+) -> Vec<ComponentKey> {
+    let chains = upstream_chains(topology, index, vec![id]);
+    topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Source && chains.contains(&node.id))
+        .map(|node| node.id.clone())
+        .collect()
+}
+
+/// Lists the transforms that no sink reads from, directly or through other transforms, in the
+/// order they appear in the topology.
+fn dead_end_transforms(topology: &Topology, index: &ComponentIndex) -> Vec<ComponentKey> {
+    let sinks = topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Sink)
+        .map(|node| node.id.clone())
+        .collect();
+    let chains = upstream_chains(topology, index, sinks);
+    topology
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ComponentKind::Transform && !chains.contains(&node.id))
+        .map(|node| node.id.clone())
+        .collect()
+}
+
+/// Collects the seeds along with every component upstream or downstream of any of them, only
+/// passing through components for which `within` is true.
+fn neighborhoods(
+    index: &ComponentIndex,
+    seeds: &[ComponentKey],
+    within: impl Fn(&ComponentKey) -> bool,
+) -> HashSet<ComponentKey> {
+    let mut neighborhoods = index.reachable(seeds.iter().cloned(), Direction::Upstream, &within);
+    neighborhoods.extend(index.reachable(seeds.iter().cloned(), Direction::Downstream, &within));
+    neighborhoods
+}
+
+/// Splits a format given after a `:` off the end of a `--config` path, such as `vector.conf:toml`.
+/// Anything else after a `:` is part of the path.
+fn split_format_suffix(path: &Path) -> Option<(PathBuf, config::Format)> {
+    let (path, suffix) = path.to_str()?.rsplit_once(':')?;
+    let format = match suffix {
+        "toml" => config::Format::Toml,
+        "json" => config::Format::Json,
+        "yaml" | "yml" => config::Format::Yaml,
+        _ => return None,
+    };
+    (!path.is_empty()).then(|| (path.into(), format))
+}
+
+/// Parses a `--relabel` value of the form `OLD=NEW`.
+fn parse_relabel(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((id, label)) if !id.is_empty() => Ok((id.to_owned(), label.to_owned())),
+        _ => Err(format!("expected OLD=NEW, got {:?}", value)),
+    }
+}
+
+/// Parses a `--node-id-prefix`, rejecting characters Mermaid would have to replace in IDs.
+fn parse_id_prefix(value: &str) -> Result<String, String> {
+    match value
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+        Some(c) => Err(format!(
+            "{:?} can't be used in node IDs; use only ASCII letters, digits, and `_`",
+            c
+        )),
+        None => Ok(value.to_owned()),
+    }
+}
+
+/// Reads a `--palette` file, as JSON if its name ends in `.json` and as TOML otherwise.
+fn read_palette(path: &Path) -> Result<Palette, String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    if path
+        .extension()
+        .map_or(false, |extension| extension == "json")
+    {
+        serde_json::from_str(&text).map_err(|error| error.to_string())
+    } else {
+        toml::from_str(&text).map_err(|error| error.to_string())
+    }
+}
+
+/// Collects the given components along with every component directly connected to them.
+fn with_neighbors(
+    topology: &Topology,
+    index: &ComponentIndex,
+    ids: &HashSet<ComponentKey>,
+) -> HashSet<ComponentKey> {
+    let present = topology.ids();
+    let mut neighbors = ids.clone();
+    for id in ids {
+        for direction in [Direction::Upstream, Direction::Downstream] {
+            neighbors.extend(
+                index
+                    .adjacent(id, direction)
+                    .iter()
+                    .filter(|neighbor| present.contains(neighbor))
+                    .cloned(),
+            );
+        }
+    }
+    neighbors
+}
+
+/// Reads component IDs from a file with one ID per line, skipping blank lines and `#` comments.
+fn read_seeds(path: &Path) -> io::Result<Vec<ComponentKey>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ComponentKey::from)
+        .collect())
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of times the graph command has loaded a config on this thread, so tests can
+    /// check it happens once per run. Each `#[tokio::test]` drives the command on its own
+    /// thread, so tests running in parallel don't count each other's loads.
+    static CONFIG_LOADS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Loads the config builder along with its warnings and, with `--show-origin`, where each
+/// component was defined.
+fn load_builder(
+    opts: &Opts,
+    paths: &[config::ConfigPath],
+    stdin: Option<&(String, config::Format)>,
+) -> Result<
+    (
+        config::ConfigBuilder,
+        Vec<String>,
+        HashMap<ComponentKey, PathBuf>,
+    ),
+    Vec<String>,
+> {
+    #[cfg(test)]
+    CONFIG_LOADS.with(|loads| loads.set(loads.get() + 1));
+
+    let with_origins = opts.show_origin || opts.print_merged_components || opts.lint;
+    let (mut builder, mut warnings, origins) = if with_origins || opts.jobs.get() > 1 {
+        load_builder_by_path(paths, opts.jobs, with_origins)?
+    } else {
+        config::load_builder_from_paths(paths)
+            .map(|(builder, warnings)| (builder, warnings, HashMap::new()))?
+    };
+
+    // Components read from stdin have no file to be labeled with, so they get no origin.
+    if let Some((contents, format)) = stdin {
+        let (part, warns) = config::load(contents.as_bytes(), *format)?;
+        warnings.extend(warns);
+        builder.append(part)?;
+    }
+    Ok((builder, warnings, origins))
+}
+
+/// Loads the config one path at a time, spread across `jobs` threads, optionally recording the
+/// file each component was defined in.
+///
+/// The per-path builders are appended in order, which is the same merge
+/// `load_builder_from_paths` performs, so the resulting builder is identical.
+fn load_builder_by_path(
+    paths: &[config::ConfigPath],
+    jobs: NonZeroUsize,
+    with_origins: bool,
+) -> Result<
+    (
+        config::ConfigBuilder,
+        Vec<String>,
+        HashMap<ComponentKey, PathBuf>,
+    ),
+    Vec<String>,
+> {
+    let mut builder = config::ConfigBuilder::default();
+    let mut origins = HashMap::new();
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    for (path, loaded) in paths.iter().zip(load_parts(paths, jobs)) {
+        let (part, warns) = match loaded {
+            Ok(loaded) => loaded,
+            Err(errs) => {
+                errors.extend(errs);
+                continue;
+            }
+        };
+
+        if with_origins {
+            let files = match path {
+                config::ConfigPath::File(..) => HashMap::new(),
+                config::ConfigPath::Dir(_) => file_origins(path),
+            };
+            let keys = part
+                .sources
+                .keys()
+                .chain(part.transforms.keys())
+                .chain(part.sinks.keys());
+            for key in keys {
+                // A component that can't be traced to a file in a directory gets the directory.
+                let origin = files.get(key).unwrap_or_else(|| path.into());
+                origins.insert(key.clone(), origin.clone());
+            }
+        }
+
+        warnings.extend(warns);
+        if let Err(errs) = builder.append(part) {
+            errors.extend(errs);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok((builder, warnings, origins))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses each path into its own builder, splitting the paths into contiguous runs for up to
+/// `jobs` threads. The results are in path order.
+fn load_parts(
+    paths: &[config::ConfigPath],
+    jobs: NonZeroUsize,
+) -> Vec<Result<(config::ConfigBuilder, Vec<String>), Vec<String>>> {
+    let load =
+        |path: &config::ConfigPath| config::load_builder_from_paths(std::slice::from_ref(path));
+    if jobs.get() == 1 || paths.len() < 2 {
+        return paths.iter().map(load).collect();
+    }
+
+    let run = (paths.len() + jobs.get() - 1) / jobs.get();
+    thread::scope(|scope| {
+        // Every thread has to be spawned before the first one is joined.
+        #[allow(clippy::needless_collect)]
+        let threads = paths
+            .chunks(run)
+            .map(|run| scope.spawn(move || run.iter().map(load).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().expect("config loading thread panicked"))
+            .collect()
+    })
+}
+
+/// Loads the config given to `--compare-to` and builds and narrows its topology the way the
+/// drawn one is, so the comparison only shows what changed between the configs.
+fn load_compared(
+    opts: &Opts,
+    seeds: &[ComponentKey],
+    changed: Option<&HashSet<ComponentKey>>,
+) -> Result<Topology, exitcode::ExitCode> {
+    let paths = opts
+        .compare_to
+        .iter()
+        .map(|path| config::ConfigPath::File(path.clone(), None))
+        .collect::<Vec<_>>();
+    let paths = match config::process_paths(&paths) {
+        Some(paths) => paths,
+        None => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("No config files to compare to.");
+            }
+            return Err(exitcode::CONFIG);
+        }
+    };
+    let (builder, mut warnings) = match config::load_builder_from_paths(&paths) {
+        Ok(loaded) => loaded,
+        Err(errs) => {
+            #[allow(clippy::print_stderr)]
+            for err in errs {
+                eprintln!("{}", err);
+            }
+            return Err(exitcode::CONFIG);
+        }
+    };
+
+    let empty =
+        builder.sources.is_empty() && builder.transforms.is_empty() && builder.sinks.is_empty();
+    let mut index = ComponentIndex::new(&builder);
+    // A component given to `--filter` may be new, so only the ones the old config has select
+    // its neighborhoods. If it has none of them, none of it is in the narrowed graph.
+    let old_seeds = seeds
+        .iter()
+        .filter(|id| index.get(id).is_some())
+        .cloned()
+        .collect::<Vec<_>>();
+    if !seeds.is_empty() && old_seeds.is_empty() {
+        return Ok(Topology::default());
+    }
+
+    let (mut topology, _) =
+        build_topology(opts, builder, empty, &old_seeds, &mut index, &mut warnings)?;
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+    narrow(opts, &mut topology, &index, &old_seeds, changed)?;
+    Ok(topology)
+}
+
+/// Makes every origin relative to the deepest directory they are all in.
+fn strip_common_dir(origins: &mut HashMap<ComponentKey, PathBuf>) {
+    let mut dirs = origins
+        .values()
+        .map(|origin| origin.parent().unwrap_or(origin));
+    let mut common = match dirs.next() {
+        Some(dir) => dir.to_owned(),
+        None => return,
+    };
+    for dir in dirs {
+        while !dir.starts_with(&common) {
+            if !common.pop() {
+                return;
+            }
+        }
+    }
+
+    for origin in origins.values_mut() {
+        if let Ok(relative) = origin.strip_prefix(&common) {
+            *origin = relative.to_owned();
+        }
+    }
+}
+
+/// The file each component loaded from `path` is defined in, found by parsing each of its
+/// files on its own. A component defined in its own file in a component subdirectory, such as
+/// `sinks/out.toml`, is credited to that file rather than any parts nested beside it.
+fn file_origins(path: &config::ConfigPath) -> HashMap<ComponentKey, PathBuf> {
+    let mut origins = HashMap::new();
+    for file in files::config_files(std::slice::from_ref(path)) {
+        match file.defines {
+            files::Defines::Config => {
+                let part = fs::read(&file.path).ok().and_then(|contents| {
+                    config::load::<_, config::ConfigBuilder>(contents.as_slice(), file.format).ok()
+                });
+                if let Some((part, _)) = part {
+                    let keys = part
+                        .sources
+                        .keys()
+                        .chain(part.transforms.keys())
+                        .chain(part.sinks.keys());
+                    for key in keys {
+                        origins
+                            .entry(key.clone())
+                            .or_insert_with(|| file.path.clone());
+                    }
+                }
+            }
+            files::Defines::Component(_, id) => {
+                origins.entry(id).or_insert(file.path);
+            }
+            files::Defines::Other => {}
+        }
+    }
+    origins
+}
@@ -0,0 +1,210 @@
+//! Settings shared by every renderer.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::{Cluster, Edge, JsonShape, Styles};
+use crate::config::ComponentKey;
+
+/// How to render a topology, built up with `GraphOptions::new().quote_ids(false)...`. Every
+/// renderer reads the settings that apply to its format and ignores the rest.
+#[derive(Clone, Debug)]
+pub struct GraphOptions {
+    /// The file each component was defined in, added to its label.
+    pub origins: HashMap<ComponentKey, PathBuf>,
+
+    /// Whether to quote DOT node IDs. If `false`, callers must first check that every ID is a
+    /// valid bare identifier with [`unquotable_ids`](super::unquotable_ids).
+    pub quote_ids: bool,
+
+    /// A string prepended to every node ID written out, but not to labels, so several graphs
+    /// can be combined without their IDs clashing.
+    pub id_prefix: Option<String>,
+
+    /// Styles overriding the defaults for individual nodes and edges.
+    pub styles: Styles,
+
+    /// Sinks with end-to-end acknowledgements enabled, whose incoming edges are tagged `[ack]`.
+    pub acknowledged: HashSet<ComponentKey>,
+
+    /// The type of each component, such as `remap`, shown in HTML labels.
+    pub component_types: HashMap<ComponentKey, String>,
+
+    /// Whether to label DOT nodes with HTML-like tables, showing the name on a title bar
+    /// colored by kind and the type and origin below it.
+    pub html_labels: bool,
+
+    /// Whether to give DOT edges a tooltip naming the components they connect and their types,
+    /// which SVG viewers show on hover.
+    pub edge_tooltips: bool,
+
+    /// Whether to write DOT graphs as `strict digraph`, which GraphViz draws with at most one
+    /// edge between any two nodes.
+    pub strict_dot: bool,
+
+    /// Whether to lay DOT graphs out left to right with every source in the leftmost rank and
+    /// every sink in the rightmost one.
+    pub align_io: bool,
+
+    /// Groups of components to draw boxes around. A component should be in at most one.
+    pub clusters: Vec<Cluster>,
+
+    /// A JSON object of Mermaid configuration, written verbatim as an `%%{init: ...}%%`
+    /// directive.
+    pub mermaid_init: Option<String>,
+
+    /// The background color of DOT graphs, such as `transparent` or `#1e1e1e`.
+    pub bgcolor: Option<String>,
+
+    /// The settings of each component as `(key, value)` pairs, listed as members in Mermaid
+    /// class diagrams.
+    pub settings: HashMap<ComponentKey, Vec<(String, String)>>,
+
+    /// How to lay out JSON output.
+    pub json_shape: JsonShape,
+
+    /// Whether to annotate nodes in JSON output with their layer, as computed by
+    /// [`Topology::layers`](super::Topology::layers).
+    pub layers: bool,
+
+    /// Whether to prefix labels with an icon for the component's kind: an emoji in Mermaid and
+    /// the kind's name in DOT.
+    pub icons: bool,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        Self {
+            origins: HashMap::new(),
+            quote_ids: true,
+            id_prefix: None,
+            styles: Styles::default(),
+            acknowledged: HashSet::new(),
+            component_types: HashMap::new(),
+            html_labels: false,
+            edge_tooltips: false,
+            align_io: false,
+            strict_dot: false,
+            clusters: Vec::new(),
+            mermaid_init: None,
+            bgcolor: None,
+            settings: HashMap::new(),
+            icons: false,
+            json_shape: JsonShape::Graph,
+            layers: false,
+        }
+    }
+}
+
+impl GraphOptions {
+    /// The default options: IDs are quoted, and nothing is annotated or styled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn origins(mut self, origins: HashMap<ComponentKey, PathBuf>) -> Self {
+        self.origins = origins;
+        self
+    }
+
+    pub const fn quote_ids(mut self, quote_ids: bool) -> Self {
+        self.quote_ids = quote_ids;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn id_prefix(mut self, prefix: Option<String>) -> Self {
+        self.id_prefix = prefix;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn styles(mut self, styles: Styles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn acknowledged(mut self, sinks: HashSet<ComponentKey>) -> Self {
+        self.acknowledged = sinks;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn mermaid_init(mut self, init: Option<String>) -> Self {
+        self.mermaid_init = init;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn bgcolor(mut self, color: Option<String>) -> Self {
+        self.bgcolor = color;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn component_types(mut self, types: HashMap<ComponentKey, String>) -> Self {
+        self.component_types = types;
+        self
+    }
+
+    pub const fn html_labels(mut self, html_labels: bool) -> Self {
+        self.html_labels = html_labels;
+        self
+    }
+
+    pub const fn edge_tooltips(mut self, edge_tooltips: bool) -> Self {
+        self.edge_tooltips = edge_tooltips;
+        self
+    }
+
+    pub const fn strict_dot(mut self, strict_dot: bool) -> Self {
+        self.strict_dot = strict_dot;
+        self
+    }
+
+    pub const fn align_io(mut self, align_io: bool) -> Self {
+        self.align_io = align_io;
+        self
+    }
+
+    pub const fn icons(mut self, icons: bool) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn settings(mut self, settings: HashMap<ComponentKey, Vec<(String, String)>>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn clusters(mut self, clusters: Vec<Cluster>) -> Self {
+        self.clusters = clusters;
+        self
+    }
+
+    pub const fn json_shape(mut self, json_shape: JsonShape) -> Self {
+        self.json_shape = json_shape;
+        self
+    }
+
+    pub const fn layers(mut self, layers: bool) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// The text to label an edge with: its port, followed by `[ack]` if it feeds an acknowledged
+    /// sink.
+    pub fn edge_label(&self, edge: &Edge) -> Option<String> {
+        let ack = self.acknowledged.contains(&edge.target);
+        match &edge.port {
+            Some(port) if ack => Some(format!("{} [ack]", port)),
+            Some(port) => Some(port.clone()),
+            None if ack => Some("[ack]".to_owned()),
+            None => None,
+        }
+    }
+}
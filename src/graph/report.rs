@@ -0,0 +1,115 @@
+//! Summarizes the health of a config as metrics and findings, for `--report`.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use super::{
+    dangling_inputs, dead_end_transforms, misused_ports, share_of_transforms, ComponentIndex,
+    ComponentKind, PortMisuse, Topology,
+};
+use crate::config::{ComponentKey, ConfigBuilder};
+
+/// The metrics and findings for one config.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    metrics: Metrics,
+    findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+struct Metrics {
+    sources: usize,
+    transforms: usize,
+    sinks: usize,
+    edges: usize,
+    cycles: usize,
+    dead_end_transforms: usize,
+
+    /// The percentage of transforms that don't feed any sink, as checked by `--lint`.
+    dead_end_percent: f64,
+}
+
+/// Something wrong with the config, tagged with its `kind`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Finding {
+    /// Components that feed back into themselves.
+    Cycle { components: Vec<String> },
+
+    /// An input that doesn't refer to any source or transform.
+    DanglingInput { component: String, input: String },
+
+    /// An input naming a port its upstream component doesn't have.
+    MisusedPort {
+        component: String,
+        input: String,
+        reason: PortMisuse,
+    },
+
+    /// A transform that doesn't feed any sink, even through other transforms.
+    DeadEnd { component: String },
+}
+
+impl Report {
+    /// Reports on a config before it is built, so configs that fail to build can still be
+    /// reported on.
+    pub fn new(builder: &ConfigBuilder) -> Self {
+        let topology = Topology::from_builder(builder);
+        let index = ComponentIndex::new(builder).connect(topology.endpoints());
+        let count = |kind| {
+            topology
+                .nodes
+                .iter()
+                .filter(|node| node.kind == kind)
+                .count()
+        };
+        let name = |id: &ComponentKey| id.id().to_owned();
+
+        let cycles = topology.cycles();
+        let dead_ends = dead_end_transforms(&topology, &index);
+        let (transforms, dead_end_percent) = share_of_transforms(&topology, dead_ends.len());
+        let metrics = Metrics {
+            sources: count(ComponentKind::Source),
+            transforms,
+            sinks: count(ComponentKind::Sink),
+            edges: topology.edges.len(),
+            cycles: cycles.len(),
+            dead_end_transforms: dead_ends.len(),
+            dead_end_percent,
+        };
+
+        let cycles = cycles.into_iter().map(|cycle| Finding::Cycle {
+            components: cycle.into_iter().map(name).collect(),
+        });
+        let dangling = dangling_inputs(builder)
+            .into_iter()
+            .map(|(component, input)| Finding::DanglingInput {
+                component: name(&component),
+                input,
+            });
+        let ports = misused_ports(builder)
+            .into_iter()
+            .map(|(component, input, reason)| Finding::MisusedPort {
+                component: name(&component),
+                input,
+                reason,
+            });
+        let dead_ends = dead_ends.iter().map(|component| Finding::DeadEnd {
+            component: name(component),
+        });
+        let findings = cycles
+            .chain(dangling)
+            .chain(ports)
+            .chain(dead_ends)
+            .collect();
+
+        Self { metrics, findings }
+    }
+
+    /// Writes the report as pretty-printed JSON, followed by a newline.
+    pub fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, self)?;
+        writeln!(w)
+    }
+}
@@ -0,0 +1,138 @@
+//! Per-component and per-connection styling applied on top of a renderer's defaults.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::ComponentKind;
+use crate::config::ComponentKey;
+
+/// How a single node or edge should stand out from the rest of the graph.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Style {
+    /// The stroke color, as a CSS/GraphViz color name or `#rrggbb` value.
+    pub color: Option<String>,
+
+    /// Whether to draw a dashed rather than a solid stroke.
+    pub dashed: bool,
+
+    /// Whether to draw a thick rather than a regular stroke.
+    pub thick: bool,
+
+    /// Whether to draw an edge with an arrowhead at both ends. Nodes ignore it.
+    pub both_ends: bool,
+}
+
+impl Style {
+    /// A solid style with the given color.
+    pub fn color(color: impl Into<String>) -> Self {
+        Self {
+            color: Some(color.into()),
+            dashed: false,
+            thick: false,
+            both_ends: false,
+        }
+    }
+
+    /// This style, dashed.
+    pub const fn dashed(mut self) -> Self {
+        self.dashed = true;
+        self
+    }
+}
+
+/// The styles to apply to a topology. Nodes are keyed by ID and edges by their index in
+/// [`Topology::edges`](super::Topology::edges); anything without an entry uses the default style.
+#[derive(Clone, Debug, Default)]
+pub struct Styles {
+    /// Styles for individual nodes.
+    pub nodes: HashMap<ComponentKey, Style>,
+
+    /// Styles for individual edges.
+    pub edges: HashMap<usize, Style>,
+}
+
+/// The colors assigned to component types, chosen to stay distinguishable side by side.
+pub const PALETTE: &[&str] = &[
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+/// Picks the palette color for a component type such as `remap`. The same type always gets the
+/// same color, across runs and configs, so diagrams stay comparable.
+pub fn type_color(component_type: &str) -> &'static str {
+    // FNV-1a, which unlike the standard library's hasher is guaranteed never to change.
+    let hash = component_type
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    PALETTE[(hash % PALETTE.len() as u64) as usize]
+}
+
+/// Colors chosen for component kinds and types, such as a team's brand colors, overriding the
+/// defaults.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    /// Colors for every component of a kind, keyed by `source`, `transform`, or `sink`.
+    pub kinds: HashMap<ComponentKind, String>,
+
+    /// Colors for every component of a type, such as `remap`. These take precedence over
+    /// `kinds`.
+    pub types: HashMap<String, String>,
+}
+
+impl Palette {
+    /// The color to draw a component in, if the palette has one for its type or its kind.
+    pub fn component_color(
+        &self,
+        kind: ComponentKind,
+        component_type: Option<&str>,
+    ) -> Option<&str> {
+        component_type
+            .and_then(|component_type| self.types.get(component_type))
+            .or_else(|| self.kinds.get(&kind))
+            .map(String::as_str)
+    }
+
+    /// The color for a component type: the palette's if it has one, otherwise the same one
+    /// [`type_color`] always picks.
+    pub fn type_color(&self, component_type: &str) -> &str {
+        self.types
+            .get(component_type)
+            .map_or_else(|| type_color(component_type), String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_colors_are_stable() {
+        // Changing these would recolor every existing diagram.
+        assert_eq!(type_color("route"), "#4e79a7");
+        assert_eq!(type_color("filter"), "#bab0ac");
+    }
+
+    #[test]
+    fn palette_prefers_types_over_kinds() {
+        let palette: Palette =
+            toml::from_str("[kinds]\ntransform = \"#111111\"\n\n[types]\nremap = \"#222222\"\n")
+                .unwrap();
+
+        let transform = ComponentKind::Transform;
+        assert_eq!(
+            palette.component_color(transform, Some("remap")),
+            Some("#222222")
+        );
+        assert_eq!(
+            palette.component_color(transform, Some("filter")),
+            Some("#111111")
+        );
+        assert_eq!(palette.component_color(ComponentKind::Sink, None), None);
+        assert_eq!(palette.type_color("remap"), "#222222");
+        assert_eq!(palette.type_color("filter"), type_color("filter"));
+    }
+}
@@ -0,0 +1,652 @@
+//! The structural model of a config that every graph renderer works from.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, ComponentKey, Config, ConfigBuilder};
+
+/// The kind of a component in the topology.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    /// A source, which has no inputs.
+    Source,
+
+    /// A transform, which has both inputs and outputs.
+    Transform,
+
+    /// A sink, which has no outputs.
+    Sink,
+}
+
+/// A component in the topology.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Node {
+    /// The ID of the component.
+    pub id: ComponentKey,
+
+    /// Whether the component is a source, transform, or sink.
+    pub kind: ComponentKind,
+
+    /// A display name to show instead of the ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A connection from an upstream component output to a downstream component.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Edge {
+    /// The upstream component.
+    pub source: ComponentKey,
+
+    /// The downstream component, which lists `source` as one of its inputs.
+    pub target: ComponentKey,
+
+    /// The named output of `source` this edge reads from, if not the default output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<String>,
+}
+
+/// A connection borrowed from a config, so edges can be inspected and filtered before any are
+/// copied into a [`Topology`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EdgeRef<'a> {
+    /// The upstream component.
+    pub source: &'a ComponentKey,
+
+    /// The downstream component.
+    pub target: &'a ComponentKey,
+
+    /// The named output of `source` this edge reads from, if not the default output.
+    pub port: Option<&'a str>,
+}
+
+impl EdgeRef<'_> {
+    /// Copies the edge out of the config.
+    pub fn to_edge(self) -> Edge {
+        Edge {
+            source: self.source.clone(),
+            target: self.target.clone(),
+            port: self.port.map(str::to_owned),
+        }
+    }
+}
+
+/// Lazily yields every connection in a config without allocating, in the order they are
+/// declared: the inputs of each transform, followed by the inputs of each sink.
+///
+/// The renderers draw edges sorted instead, so use [`edges`] to get them in the order they are
+/// drawn.
+pub fn edges_iter(config: &Config) -> impl Iterator<Item = EdgeRef<'_>> {
+    let transforms = config
+        .transforms()
+        .map(|(id, transform)| (id, &transform.inputs[..]));
+    let sinks = config.sinks().map(|(id, sink)| (id, &sink.inputs[..]));
+
+    transforms.chain(sinks).flat_map(|(id, inputs)| {
+        inputs.iter().map(move |input| EdgeRef {
+            source: &input.component,
+            target: id,
+            port: input.port.as_deref(),
+        })
+    })
+}
+
+/// Iterates over every connection in a config as owned edges, in the same order the renderers
+/// draw them: sorted by source, then target, then port.
+pub fn edges(config: &Config) -> impl Iterator<Item = Edge> {
+    let mut edges = edges_iter(config).map(EdgeRef::to_edge).collect::<Vec<_>>();
+    sort_edges(&mut edges);
+    edges.into_iter()
+}
+
+/// Sorts edges by source, then target, then port.
+fn sort_edges(edges: &mut [Edge]) {
+    edges.sort_by(|a, b| (&a.source, &a.target, &a.port).cmp(&(&b.source, &b.target, &b.port)));
+}
+
+/// The components of a config and the connections between them, extracted once so renderers
+/// don't each need to walk the config.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Topology {
+    /// Every component, ordered sources, transforms, then sinks.
+    pub nodes: Vec<Node>,
+
+    /// Every connection, in the order the renderers draw them. Extracted topologies sort them by
+    /// source, target, then port, so the order inputs are listed in doesn't change the output.
+    pub edges: Vec<Edge>,
+}
+
+impl Topology {
+    /// Extracts the topology of a config. Nodes are ordered sources, transforms, then sinks, each
+    /// in config order.
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_config_where(config, |_| true)
+    }
+
+    /// Extracts only the components matching `keep`, along with the edges between them. Edges
+    /// are filtered before they are copied, so a small part of a large config is cheap to draw.
+    pub fn from_config_where(config: &Config, keep: impl Fn(&ComponentKey) -> bool) -> Self {
+        let nodes = config
+            .sources()
+            .map(|(id, _)| (id, ComponentKind::Source))
+            .chain(
+                config
+                    .transforms()
+                    .map(|(id, _)| (id, ComponentKind::Transform)),
+            )
+            .chain(config.sinks().map(|(id, _)| (id, ComponentKind::Sink)))
+            .filter(|(id, _)| keep(id))
+            .map(|(id, kind)| Node {
+                id: id.clone(),
+                kind,
+                label: None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut edges = edges_iter(config)
+            .filter(|edge| keep(edge.source) && keep(edge.target))
+            .map(EdgeRef::to_edge)
+            .collect::<Vec<_>>();
+        sort_edges(&mut edges);
+
+        Self { nodes, edges }
+    }
+
+    /// Extracts the topology of a config that hasn't been validated yet, such as one that fails to
+    /// build because it contains a cycle.
+    ///
+    /// Input globs are expanded the same way building the config would. Inputs that don't match
+    /// any component are left out.
+    pub fn from_builder(builder: &ConfigBuilder) -> Self {
+        let mut builder = builder.clone();
+        config::expand_globs(&mut builder);
+
+        let nodes = builder
+            .sources
+            .keys()
+            .map(|id| (id, ComponentKind::Source))
+            .chain(
+                builder
+                    .transforms
+                    .keys()
+                    .map(|id| (id, ComponentKind::Transform)),
+            )
+            .chain(builder.sinks.keys().map(|id| (id, ComponentKind::Sink)))
+            .map(|(id, kind)| Node {
+                id: id.clone(),
+                kind,
+                label: None,
+            })
+            .collect::<Vec<_>>();
+
+        let is_upstream = |key: &ComponentKey| {
+            builder.sources.contains_key(key) || builder.transforms.contains_key(key)
+        };
+        let resolve = |input: &str| {
+            let key = ComponentKey::from(input);
+            if is_upstream(&key) {
+                return Some((key, None));
+            }
+            input.rsplit_once('.').and_then(|(component, port)| {
+                let key = ComponentKey::from(component);
+                is_upstream(&key).then(|| (key, Some(port.to_owned())))
+            })
+        };
+
+        let transforms = builder
+            .transforms
+            .iter()
+            .map(|(id, transform)| (id, &transform.inputs[..]));
+        let sinks = builder
+            .sinks
+            .iter()
+            .map(|(id, sink)| (id, &sink.inputs[..]));
+        let mut edges = transforms
+            .chain(sinks)
+            .flat_map(|(id, inputs)| {
+                inputs.iter().filter_map(move |input| {
+                    resolve(input).map(|(source, port)| Edge {
+                        source,
+                        target: id.clone(),
+                        port,
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        sort_edges(&mut edges);
+
+        Self { nodes, edges }
+    }
+
+    /// Finds the groups of components that feed back into themselves. Each group lists its
+    /// components in node order, and groups are ordered by their first component.
+    pub fn cycles(&self) -> Vec<Vec<&ComponentKey>> {
+        let self_loops = self
+            .edges
+            .iter()
+            .filter(|edge| edge.source == edge.target)
+            .map(|edge| &edge.source)
+            .collect::<HashSet<_>>();
+        let mut components = self.strongly_connected();
+        components
+            .retain(|members| members.len() > 1 || self_loops.contains(&self.nodes[members[0]].id));
+        components.sort_unstable_by_key(|members| members[0]);
+
+        components
+            .into_iter()
+            .map(|members| {
+                members
+                    .into_iter()
+                    .map(|node| &self.nodes[node].id)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The indices of the edges that are part of a cycle.
+    pub fn cycle_edges(&self) -> HashSet<usize> {
+        let mut component_of = HashMap::new();
+        for (index, members) in self.strongly_connected().iter().enumerate() {
+            for &node in members {
+                component_of.insert(&self.nodes[node].id, index);
+            }
+        }
+
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| {
+                matches!(
+                    (component_of.get(&edge.source), component_of.get(&edge.target)),
+                    (Some(source), Some(target)) if source == target
+                )
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The layer of every node: 0 for nodes without inputs, and otherwise one more than the
+    /// deepest of its inputs, so every edge points to a higher layer. Returns `None` if the
+    /// topology has a cycle, since its nodes can't be layered.
+    pub fn layers(&self) -> Option<HashMap<&ComponentKey, usize>> {
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (&node.id, index))
+            .collect::<HashMap<_, _>>();
+        let mut downstream = vec![Vec::new(); self.nodes.len()];
+        let mut pending = vec![0; self.nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&source), Some(&target)) =
+                (index.get(&edge.source), index.get(&edge.target))
+            {
+                downstream[source].push(target);
+                pending[target] += 1;
+            }
+        }
+
+        // Kahn's algorithm: a node is layered once every one of its inputs is.
+        let mut layers = vec![0; self.nodes.len()];
+        let mut ready = (0..self.nodes.len())
+            .filter(|&node| pending[node] == 0)
+            .collect::<Vec<_>>();
+        let mut layered = 0;
+        while let Some(node) = ready.pop() {
+            layered += 1;
+            for &successor in &downstream[node] {
+                layers[successor] = layers[successor].max(layers[node] + 1);
+                pending[successor] -= 1;
+                if pending[successor] == 0 {
+                    ready.push(successor);
+                }
+            }
+        }
+
+        (layered == self.nodes.len())
+            .then(|| self.nodes.iter().map(|node| &node.id).zip(layers).collect())
+    }
+
+    /// Partitions the nodes into strongly connected components using Kosaraju's algorithm, with
+    /// both passes iterative so deep chains can't overflow the stack. Members of each component
+    /// are node indices in ascending order.
+    fn strongly_connected(&self) -> Vec<Vec<usize>> {
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (&node.id, index))
+            .collect::<HashMap<_, _>>();
+        let mut downstream = vec![Vec::new(); self.nodes.len()];
+        let mut upstream = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&source), Some(&target)) =
+                (index.get(&edge.source), index.get(&edge.target))
+            {
+                downstream[source].push(target);
+                upstream[target].push(source);
+            }
+        }
+
+        // Order the nodes by when their depth-first traversal finishes.
+        let mut finished = Vec::with_capacity(self.nodes.len());
+        let mut visited = vec![false; self.nodes.len()];
+        for start in 0..self.nodes.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some((node, next)) = stack.last_mut() {
+                if let Some(&successor) = downstream[*node].get(*next) {
+                    *next += 1;
+                    if !visited[successor] {
+                        visited[successor] = true;
+                        stack.push((successor, 0));
+                    }
+                } else {
+                    finished.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+
+        // Walking upstream in reverse finishing order collects one component at a time.
+        let mut assigned = vec![false; self.nodes.len()];
+        let mut components = Vec::new();
+        for &start in finished.iter().rev() {
+            if assigned[start] {
+                continue;
+            }
+            assigned[start] = true;
+            let mut members = vec![start];
+            let mut next = 0;
+            while let Some(&node) = members.get(next) {
+                for &predecessor in &upstream[node] {
+                    if !assigned[predecessor] {
+                        assigned[predecessor] = true;
+                        members.push(predecessor);
+                    }
+                }
+                next += 1;
+            }
+            members.sort_unstable();
+            components.push(members);
+        }
+
+        components
+    }
+
+    /// Lists every path from a source to a component with no outputs, at most `max` of them.
+    /// Paths follow edges in order, and one that runs into a cycle ends where it would repeat
+    /// a component. The second value is whether paths were left out to stay within `max`.
+    pub fn chains(&self, max: usize) -> (Vec<Vec<&ComponentKey>>, bool) {
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (&node.id, index))
+            .collect::<HashMap<_, _>>();
+        let mut outputs = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&source), Some(&target)) =
+                (index.get(&edge.source), index.get(&edge.target))
+            {
+                // Edges from different ports of the same component lead down the same path.
+                if !outputs[source].contains(&target) {
+                    outputs[source].push(target);
+                }
+            }
+        }
+
+        let mut chains = Vec::new();
+        let mut on_path = vec![false; self.nodes.len()];
+        let roots =
+            (0..self.nodes.len()).filter(|&node| self.nodes[node].kind == ComponentKind::Source);
+        for root in roots {
+            on_path[root] = true;
+            let mut stack = vec![(root, 0)];
+            if outputs[root].is_empty() {
+                if chains.len() == max {
+                    return (chains, true);
+                }
+                chains.push(vec![&self.nodes[root].id]);
+            }
+
+            while let Some((node, next)) = stack.last_mut() {
+                let child = match outputs[*node].get(*next) {
+                    Some(&child) => child,
+                    None => {
+                        on_path[*node] = false;
+                        stack.pop();
+                        continue;
+                    }
+                };
+                *next += 1;
+                if on_path[child] {
+                    continue;
+                }
+
+                on_path[child] = true;
+                stack.push((child, 0));
+                if outputs[child].iter().all(|&output| on_path[output]) {
+                    if chains.len() == max {
+                        return (chains, true);
+                    }
+                    chains.push(
+                        stack
+                            .iter()
+                            .map(|&(node, _)| &self.nodes[node].id)
+                            .collect(),
+                    );
+                }
+            }
+        }
+
+        (chains, false)
+    }
+
+    /// The upstream and downstream component of every edge, in edge order, to index with
+    /// [`ComponentIndex::connect`](super::ComponentIndex::connect).
+    pub fn endpoints(&self) -> impl Iterator<Item = (&ComponentKey, &ComponentKey)> {
+        self.edges.iter().map(|edge| (&edge.source, &edge.target))
+    }
+
+    /// The IDs of every node, so analyses can tell which components are still in the topology
+    /// after it has been narrowed down.
+    pub fn ids(&self) -> HashSet<&ComponentKey> {
+        self.nodes.iter().map(|node| &node.id).collect()
+    }
+
+    /// The number of groups of nodes that are connected by edges followed in either direction.
+    /// An empty topology has none.
+    pub fn connected_components(&self) -> usize {
+        let mut neighbors = HashMap::<_, Vec<_>>::new();
+        for edge in &self.edges {
+            neighbors
+                .entry(&edge.source)
+                .or_default()
+                .push(&edge.target);
+            neighbors
+                .entry(&edge.target)
+                .or_default()
+                .push(&edge.source);
+        }
+
+        let mut visited = HashSet::new();
+        let mut components = 0;
+        for node in &self.nodes {
+            if !visited.insert(&node.id) {
+                continue;
+            }
+            components += 1;
+            let mut pending = vec![&node.id];
+            while let Some(id) = pending.pop() {
+                for &neighbor in neighbors.get(id).into_iter().flatten() {
+                    if visited.insert(neighbor) {
+                        pending.push(neighbor);
+                    }
+                }
+            }
+        }
+        components
+    }
+
+    /// Keeps only the nodes matching `predicate`, along with the edges between them.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&Node) -> bool) {
+        self.nodes.retain(|node| predicate(node));
+
+        let ids = self
+            .nodes
+            .iter()
+            .map(|node| &node.id)
+            .collect::<HashSet<_>>();
+        self.edges
+            .retain(|edge| ids.contains(&edge.source) && ids.contains(&edge.target));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology(nodes: &[(&str, ComponentKind)], edges: &[(&str, &str)]) -> Topology {
+        Topology {
+            nodes: nodes
+                .iter()
+                .map(|&(id, kind)| Node {
+                    id: id.into(),
+                    kind,
+                    label: None,
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|&(source, target)| Edge {
+                    source: source.into(),
+                    target: target.into(),
+                    port: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let topology = topology(
+            &[
+                ("in", ComponentKind::Source),
+                ("one", ComponentKind::Transform),
+                ("two", ComponentKind::Transform),
+                ("three", ComponentKind::Transform),
+                ("self", ComponentKind::Transform),
+                ("out", ComponentKind::Sink),
+            ],
+            &[
+                ("in", "one"),
+                ("three", "one"),
+                ("one", "two"),
+                ("two", "three"),
+                ("in", "self"),
+                ("self", "self"),
+                ("two", "out"),
+                ("self", "out"),
+            ],
+        );
+
+        let expected: Vec<Vec<ComponentKey>> = vec![
+            vec!["one".into(), "two".into(), "three".into()],
+            vec!["self".into()],
+        ];
+        let cycles = topology
+            .cycles()
+            .into_iter()
+            .map(|cycle| cycle.into_iter().cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(cycles, expected);
+        assert_eq!(topology.cycle_edges(), HashSet::from([1, 2, 3, 5]));
+    }
+
+    #[test]
+    fn acyclic_topology_has_no_cycles() {
+        let topology = topology(
+            &[
+                ("in", ComponentKind::Source),
+                ("one", ComponentKind::Transform),
+                ("two", ComponentKind::Transform),
+                ("out", ComponentKind::Sink),
+            ],
+            &[("in", "one"), ("in", "two"), ("one", "two"), ("two", "out")],
+        );
+
+        assert!(topology.cycles().is_empty());
+        assert!(topology.cycle_edges().is_empty());
+    }
+
+    #[test]
+    fn lists_chains_from_sources() {
+        let topology = topology(
+            &[
+                ("in", ComponentKind::Source),
+                ("idle", ComponentKind::Source),
+                ("parse", ComponentKind::Transform),
+                ("loop", ComponentKind::Transform),
+                ("out", ComponentKind::Sink),
+                ("archive", ComponentKind::Sink),
+            ],
+            &[
+                ("in", "parse"),
+                ("parse", "out"),
+                ("parse", "out"),
+                ("in", "loop"),
+                ("loop", "loop"),
+                ("loop", "archive"),
+                ("parse", "archive"),
+            ],
+        );
+
+        let chain = |ids: &[&str]| ids.iter().map(|&id| ComponentKey::from(id)).collect();
+        let expected: Vec<Vec<ComponentKey>> = vec![
+            chain(&["in", "parse", "out"]),
+            chain(&["in", "parse", "archive"]),
+            chain(&["in", "loop", "archive"]),
+            chain(&["idle"]),
+        ];
+
+        let owned = |chains: Vec<Vec<&ComponentKey>>| {
+            chains
+                .into_iter()
+                .map(|chain| chain.into_iter().cloned().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let (chains, truncated) = topology.chains(10);
+        assert_eq!(owned(chains), expected);
+        assert!(!truncated);
+
+        let (chains, truncated) = topology.chains(2);
+        assert_eq!(owned(chains), expected[..2]);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn counts_connected_components() {
+        let mut topology = topology(
+            &[
+                ("in", ComponentKind::Source),
+                ("parse", ComponentKind::Transform),
+                ("out", ComponentKind::Sink),
+                ("other", ComponentKind::Source),
+            ],
+            &[("in", "parse"), ("out", "parse")],
+        );
+        // Edges connect nodes whichever way they point.
+        assert_eq!(topology.connected_components(), 2);
+
+        topology.retain(|node| node.id.id() != "parse");
+        assert_eq!(topology.connected_components(), 3);
+
+        assert_eq!(Topology::default().connected_components(), 0);
+    }
+}
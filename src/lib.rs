@@ -71,7 +71,8 @@ pub mod encoding_transcode;
 pub mod enrichment_tables;
 #[cfg(feature = "gcp")]
 pub mod gcp;
-pub(crate) mod graph;
+#[allow(unreachable_pub)]
+pub mod graph;
 pub mod heartbeat;
 pub mod http;
 #[allow(unreachable_pub)]